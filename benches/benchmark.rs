@@ -128,6 +128,80 @@ fn table_traversal_sequence(c: &mut Criterion) {
     });
 }
 
+fn table_extend_sequence(c: &mut Criterion) {
+    let lua = Lua::new();
+
+    // `extend_sequence` cannot grow an existing table, so the table is pre-sized by the caller
+    c.bench_function("table [extend sequence]", |b| {
+        b.iter_batched(
+            || {
+                collect_gc_twice(&lua);
+                lua.create_table_with_capacity(1000, 0).unwrap()
+            },
+            |table| {
+                table.extend_sequence(1..=1000).unwrap();
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+fn table_push_sequence(c: &mut Criterion) {
+    let lua = Lua::new();
+
+    c.bench_function("table [push sequence]", |b| {
+        b.iter_batched(
+            || {
+                collect_gc_twice(&lua);
+                lua.create_table_with_capacity(1000, 0).unwrap()
+            },
+            |table| {
+                for i in 1..=1000 {
+                    table.raw_push(i).unwrap();
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+fn table_set_get_many(c: &mut Criterion) {
+    let lua = Lua::new();
+    let keys = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+
+    // `set_many` cannot grow an existing table, so the table is pre-sized by the caller
+    c.bench_function("table [set_many and get_many]", |b| {
+        b.iter_batched(
+            || {
+                collect_gc_twice(&lua);
+                lua.create_table_with_capacity(0, keys.len()).unwrap()
+            },
+            |table| {
+                table.set_many(keys.into_iter().zip(0..)).unwrap();
+                let values = table.get_many::<_, usize>(keys).unwrap();
+                assert_eq!(values.len(), keys.len());
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+fn table_to_vec(c: &mut Criterion) {
+    let lua = Lua::new();
+
+    let table = lua.create_sequence_from(1..1000).unwrap();
+
+    c.bench_function("table [to_vec]", |b| {
+        b.iter_batched(
+            || table.clone(),
+            |table| {
+                let _v = table.to_vec::<i32>().unwrap();
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
 fn function_create(c: &mut Criterion) {
     let lua = Lua::new();
 
@@ -327,6 +401,63 @@ fn userdata_call_method(c: &mut Criterion) {
     });
 }
 
+fn userdata_call_method_mixed(c: &mut Criterion) {
+    struct UserData1(i64);
+    impl LuaUserData for UserData1 {
+        fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+            methods.add_method("add", |_, this, i: i64| Ok(this.0 + i));
+        }
+    }
+
+    struct UserData2(i64);
+    impl LuaUserData for UserData2 {
+        fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+            methods.add_method("add", |_, this, i: i64| Ok(this.0 - i));
+        }
+    }
+
+    let lua = Lua::new();
+    let ud1 = lua.create_userdata(UserData1(123)).unwrap();
+    let ud2 = lua.create_userdata(UserData2(123)).unwrap();
+    let method = lua
+        .load("function(ud1, ud2, i) return ud1:add(i) + ud2:add(i) end")
+        .eval::<LuaFunction>()
+        .unwrap();
+    let i = AtomicUsize::new(0);
+
+    c.bench_function("userdata [call method mixed]", |b| {
+        b.iter_batched(
+            || {
+                collect_gc_twice(&lua);
+                i.fetch_add(1, Ordering::Relaxed)
+            },
+            |i| {
+                assert_eq!(method.call::<usize>((&ud1, &ud2, i)).unwrap(), 246);
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+fn userdata_is(c: &mut Criterion) {
+    struct UserData1(#[allow(unused)] i64);
+    impl LuaUserData for UserData1 {}
+
+    struct UserData2(#[allow(unused)] i64);
+    impl LuaUserData for UserData2 {}
+
+    let lua = Lua::new();
+    let ud1 = lua.create_userdata(UserData1(1)).unwrap();
+    let ud2 = lua.create_userdata(UserData2(2)).unwrap();
+
+    c.bench_function("userdata [is]", |b| {
+        b.iter(|| {
+            assert!(ud1.is::<UserData1>());
+            assert!(!ud2.is::<UserData1>());
+        });
+    });
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default()
@@ -341,6 +472,10 @@ criterion_group! {
         table_traversal_pairs,
         table_traversal_for_each,
         table_traversal_sequence,
+        table_extend_sequence,
+        table_push_sequence,
+        table_set_get_many,
+        table_to_vec,
 
         function_create,
         function_call_sum,
@@ -354,6 +489,8 @@ criterion_group! {
         userdata_create,
         userdata_call_index,
         userdata_call_method,
+        userdata_call_method_mixed,
+        userdata_is,
 }
 
 criterion_main!(benches);
@@ -141,5 +141,27 @@ pub unsafe fn luaL_getmetatable(L: *mut lua_State, n: *const c_char) {
 // TODO: luaL_opt
 
 //
-// TODO: Generic Buffer Manipulation
+// Generic Buffer Manipulation
 //
+
+// `LUAL_BUFFERSIZE` is defined as `BUFSIZ` which is platform-dependent.
+// We use the largest common value; the buffer is only accessed from C, so a larger struct is harmless.
+pub const LUAL_BUFFERSIZE: usize = 8192;
+
+#[repr(C)]
+pub struct luaL_Buffer {
+    pub p: *mut c_char, // current position in buffer
+    pub lvl: c_int,     // number of strings in the stack (level)
+    pub L: *mut lua_State,
+    pub buffer: [c_char; LUAL_BUFFERSIZE],
+}
+
+#[cfg_attr(all(windows, raw_dylib), link(name = "lua51", kind = "raw-dylib"))]
+unsafe extern "C-unwind" {
+    pub fn luaL_buffinit(L: *mut lua_State, B: *mut luaL_Buffer);
+    pub fn luaL_prepbuffer(B: *mut luaL_Buffer) -> *mut c_char;
+    pub fn luaL_addlstring(B: *mut luaL_Buffer, s: *const c_char, l: usize);
+    pub fn luaL_addstring(B: *mut luaL_Buffer, s: *const c_char);
+    pub fn luaL_addvalue(B: *mut luaL_Buffer);
+    pub fn luaL_pushresult(B: *mut luaL_Buffer);
+}
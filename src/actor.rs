@@ -0,0 +1,504 @@
+//! Parallel Lua workers communicating by message passing.
+
+use std::fmt;
+use std::panic::resume_unwind;
+use std::string::String as StdString;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use parking_lot::{Mutex, RwLock};
+use rustc_hash::FxHashSet;
+
+use crate::error::{Error, Result};
+use crate::interrupt::InterruptHandle;
+use crate::state::Lua;
+use crate::table::Table;
+use crate::traits::{FromLua, IntoLua};
+use crate::types::{Integer, Number};
+use crate::value::Value;
+
+type Factory = dyn Fn() -> Result<Lua> + Send + Sync;
+
+// A message with the id of the sending worker (`None` for the host)
+type Envelope = (Option<usize>, ActorValue);
+
+/// An owned, deep copy of a Lua value that can be sent between Lua states.
+///
+/// Only "plain data" can be sent: nil, booleans, numbers, strings, tables (without metatables)
+/// and, on Luau, vectors and buffers. Functions, threads and userdata cannot be sent, and tables
+/// must not contain cycles.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ActorValue {
+    /// The Lua value `nil`.
+    Nil,
+    /// The Lua value `true` or `false`.
+    Boolean(bool),
+    /// An integer number.
+    Integer(Integer),
+    /// A floating point number.
+    Number(Number),
+    /// A Lua string.
+    String(Vec<u8>),
+    /// A Lua table as a list of key-value pairs.
+    Table(Vec<(ActorValue, ActorValue)>),
+    /// A Luau vector.
+    #[cfg(feature = "luau")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
+    Vector(crate::Vector),
+    /// A Luau buffer.
+    #[cfg(feature = "luau")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
+    Buffer(Vec<u8>),
+}
+
+impl ActorValue {
+    /// Returns `true` if the value is `Nil`.
+    pub fn is_nil(&self) -> bool {
+        matches!(self, ActorValue::Nil)
+    }
+
+    /// Cast the value to `bool`.
+    pub fn as_boolean(&self) -> Option<bool> {
+        match *self {
+            ActorValue::Boolean(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Cast the value to `Integer`.
+    pub fn as_integer(&self) -> Option<Integer> {
+        match *self {
+            ActorValue::Integer(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Cast the value to `Number`.
+    ///
+    /// Integers are converted to numbers.
+    pub fn as_number(&self) -> Option<Number> {
+        match *self {
+            ActorValue::Integer(i) => Some(i as Number),
+            ActorValue::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Cast the value to `str` if it's a valid UTF-8 string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ActorValue::String(s) => std::str::from_utf8(s).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value associated with the string `key` if the value is a table.
+    pub fn get(&self, key: &str) -> Option<&ActorValue> {
+        match self {
+            ActorValue::Table(pairs) => pairs
+                .iter()
+                .find(|(k, _)| k.as_str() == Some(key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn from_value(value: Value, visited: &mut FxHashSet<usize>) -> Result<Self> {
+        match value {
+            Value::Nil => Ok(ActorValue::Nil),
+            Value::Boolean(b) => Ok(ActorValue::Boolean(b)),
+            Value::Integer(i) => Ok(ActorValue::Integer(i)),
+            Value::Number(n) => Ok(ActorValue::Number(n)),
+            Value::String(s) => Ok(ActorValue::String(s.as_bytes().to_vec())),
+            Value::Table(table) => {
+                let ptr = table.to_pointer() as usize;
+                if !visited.insert(ptr) {
+                    return Err(Error::FromLuaConversionError {
+                        from: "table",
+                        to: "ActorValue".to_string(),
+                        message: Some("recursive table detected".to_string()),
+                    });
+                }
+                if table.metatable().is_some() {
+                    return Err(Error::FromLuaConversionError {
+                        from: "table",
+                        to: "ActorValue".to_string(),
+                        message: Some("tables with metatables cannot be sent".to_string()),
+                    });
+                }
+                let mut pairs = Vec::new();
+                table.for_each::<Value, Value>(|key, value| {
+                    pairs.push((Self::from_value(key, visited)?, Self::from_value(value, visited)?));
+                    Ok(())
+                })?;
+                visited.remove(&ptr);
+                Ok(ActorValue::Table(pairs))
+            }
+            #[cfg(feature = "luau")]
+            Value::Vector(v) => Ok(ActorValue::Vector(v)),
+            #[cfg(feature = "luau")]
+            Value::Buffer(buf) => Ok(ActorValue::Buffer(buf.to_vec())),
+            _ => Err(Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: "ActorValue".to_string(),
+                message: Some("value cannot be sent to another Lua state".to_string()),
+            }),
+        }
+    }
+}
+
+impl From<bool> for ActorValue {
+    #[inline]
+    fn from(b: bool) -> Self {
+        ActorValue::Boolean(b)
+    }
+}
+
+impl From<Integer> for ActorValue {
+    #[inline]
+    fn from(i: Integer) -> Self {
+        ActorValue::Integer(i)
+    }
+}
+
+impl From<Number> for ActorValue {
+    #[inline]
+    fn from(n: Number) -> Self {
+        ActorValue::Number(n)
+    }
+}
+
+impl From<&str> for ActorValue {
+    #[inline]
+    fn from(s: &str) -> Self {
+        ActorValue::String(s.as_bytes().to_vec())
+    }
+}
+
+impl From<StdString> for ActorValue {
+    #[inline]
+    fn from(s: StdString) -> Self {
+        ActorValue::String(s.into_bytes())
+    }
+}
+
+impl IntoLua for ActorValue {
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        Ok(match self {
+            ActorValue::Nil => Value::Nil,
+            ActorValue::Boolean(b) => Value::Boolean(b),
+            ActorValue::Integer(i) => Value::Integer(i),
+            ActorValue::Number(n) => Value::Number(n),
+            ActorValue::String(s) => Value::String(lua.create_string(s)?),
+            ActorValue::Table(pairs) => {
+                let table = lua.create_table_with_capacity(0, pairs.len())?;
+                for (key, value) in pairs {
+                    table.raw_set(key, value)?;
+                }
+                Value::Table(table)
+            }
+            #[cfg(feature = "luau")]
+            ActorValue::Vector(v) => Value::Vector(v),
+            #[cfg(feature = "luau")]
+            ActorValue::Buffer(buf) => Value::Buffer(lua.create_buffer(buf)?),
+        })
+    }
+}
+
+impl FromLua for ActorValue {
+    fn from_lua(value: Value, _: &Lua) -> Result<Self> {
+        Self::from_value(value, &mut FxHashSet::default())
+    }
+}
+
+// Inboxes of all workers, cleared when the host closes them
+struct Mailboxes(RwLock<Vec<Sender<Envelope>>>);
+
+// Interrupt handles of the worker Lua states, used to stop the workers when `Actors` is dropped
+#[derive(Default)]
+struct Interrupts {
+    stopped: bool,
+    handles: Vec<InterruptHandle>,
+}
+
+impl Interrupts {
+    fn register(&mut self, handle: InterruptHandle) {
+        if self.stopped {
+            handle.interrupt();
+        }
+        self.handles.push(handle);
+    }
+
+    fn interrupt_all(&mut self) {
+        self.stopped = true;
+        for handle in &self.handles {
+            handle.interrupt();
+        }
+    }
+}
+
+impl Mailboxes {
+    fn send(&self, worker: usize, envelope: Envelope) -> Result<()> {
+        let senders = self.0.read();
+        let sender = senders
+            .get(worker)
+            .ok_or_else(|| Error::runtime(format!("actor worker {worker} is not available")))?;
+        sender
+            .send(envelope)
+            .map_err(|_| Error::runtime(format!("actor worker {worker} is not running")))
+    }
+}
+
+/// A group of Lua states running the same code in parallel on worker OS threads.
+///
+/// Each worker gets its own Lua state and exposes an `actor` library to the worker script:
+///
+/// - `actor.id`: index of the current worker (starting from 0).
+/// - `actor.count`: number of workers.
+/// - `actor.send(value)`: sends `value` to the host, and returns `false` if the host is gone.
+/// - `actor.post(id, value)`: sends `value` to another worker, and returns `false` if the worker is
+///   not running.
+/// - `actor.receive([timeout])`: waits for a message (for at most `timeout` seconds, if given) and
+///   returns the message and the id of the sending worker (`nil` for the host). Returns `nil` if
+///   the timeout has elapsed or the mailboxes were closed by the host.
+///
+/// Messages are deep copied between Lua states (see [`ActorValue`]).
+///
+/// Dropping `Actors` without calling [`Actors::join`] closes the mailboxes and interrupts
+/// scripts that are still running (see [`InterruptHandle`]), then detaches the worker threads.
+///
+/// Requires `feature = "actor"`.
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{ActorValue, Actors, Result};
+/// # fn main() -> Result<()> {
+/// let actors = Actors::new(2, r#"
+///     while true do
+///         local n = actor.receive()
+///         if n == nil then break end
+///         actor.send(n * n)
+///     end
+/// "#)?;
+///
+/// actors.send(0, ActorValue::Integer(3))?;
+/// actors.send(1, ActorValue::Integer(4))?;
+/// let mut results = vec![
+///     actors.receive().unwrap().1.as_integer(),
+///     actors.receive().unwrap().1.as_integer(),
+/// ];
+/// results.sort();
+/// assert_eq!(results, vec![Some(9), Some(16)]);
+///
+/// actors.join()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Actors {
+    mailboxes: Arc<Mailboxes>,
+    inbox: Mutex<Receiver<(usize, ActorValue)>>,
+    interrupts: Arc<Mutex<Interrupts>>,
+    workers: Vec<JoinHandle<Result<()>>>,
+}
+
+impl Actors {
+    /// Spawns `workers` Lua states (created by [`Lua::new`]) and runs `source` in each of them.
+    pub fn new(workers: usize, source: impl Into<Vec<u8>>) -> Result<Self> {
+        Self::with_factory(workers, source, || Ok(Lua::new()))
+    }
+
+    /// Spawns `workers` Lua states created by `factory` and runs `source` in each of them.
+    ///
+    /// The factory is called on the worker threads.
+    pub fn with_factory<F>(workers: usize, source: impl Into<Vec<u8>>, factory: F) -> Result<Self>
+    where
+        F: Fn() -> Result<Lua> + Send + Sync + 'static,
+    {
+        let source: Arc<[u8]> = source.into().into();
+        let factory: Arc<Factory> = Arc::new(factory);
+        let (outbox, inbox) = mpsc::channel();
+
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..workers).map(|_| mpsc::channel()).unzip();
+        let mailboxes = Arc::new(Mailboxes(RwLock::new(senders)));
+        let interrupts = Arc::new(Mutex::new(Interrupts::default()));
+
+        let mut actors = Actors {
+            mailboxes: mailboxes.clone(),
+            inbox: Mutex::new(inbox),
+            interrupts: interrupts.clone(),
+            workers: Vec::with_capacity(workers),
+        };
+        for (id, receiver) in receivers.into_iter().enumerate() {
+            let worker = Worker {
+                id,
+                count: workers,
+                mailboxes: mailboxes.clone(),
+                inbox: receiver,
+                outbox: outbox.clone(),
+                interrupts: interrupts.clone(),
+            };
+            let (source, factory) = (source.clone(), factory.clone());
+            let handle = std::thread::Builder::new()
+                .name(format!("lua-actor-{id}"))
+                .spawn(move || worker.run(&source, &*factory))
+                .map_err(Error::external)?;
+            actors.workers.push(handle);
+        }
+        Ok(actors)
+    }
+
+    /// Returns the number of workers.
+    pub fn worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Sends a message to the worker with the given index.
+    pub fn send(&self, worker: usize, value: impl Into<ActorValue>) -> Result<()> {
+        self.mailboxes.send(worker, (None, value.into()))
+    }
+
+    /// Sends a message to all running workers.
+    pub fn broadcast(&self, value: impl Into<ActorValue>) {
+        let value = value.into();
+        for sender in self.mailboxes.0.read().iter() {
+            let _ = sender.send((None, value.clone()));
+        }
+    }
+
+    /// Waits for a message from a worker.
+    ///
+    /// Returns the index of the sending worker and the message, or `None` if all workers have
+    /// finished.
+    pub fn receive(&self) -> Option<(usize, ActorValue)> {
+        self.inbox.lock().recv().ok()
+    }
+
+    /// Waits for a message from a worker for at most `timeout`.
+    ///
+    /// Returns `None` if the timeout has elapsed or all workers have finished.
+    pub fn receive_timeout(&self, timeout: Duration) -> Option<(usize, ActorValue)> {
+        self.inbox.lock().recv_timeout(timeout).ok()
+    }
+
+    /// Returns a pending message from a worker, if any, without blocking.
+    pub fn try_receive(&self) -> Option<(usize, ActorValue)> {
+        self.inbox.lock().try_recv().ok()
+    }
+
+    /// Closes the worker mailboxes.
+    ///
+    /// Pending messages are still delivered, after which `actor.receive()` returns `nil`.
+    pub fn close(&self) {
+        self.mailboxes.0.write().clear();
+    }
+
+    /// Closes the worker mailboxes and waits for all workers to finish.
+    ///
+    /// Returns the first error raised by a worker script. If a worker panicked, the panic is
+    /// propagated to the caller.
+    pub fn join(mut self) -> Result<()> {
+        self.close();
+        let mut result = Ok(());
+        for handle in self.workers.drain(..) {
+            match handle.join() {
+                Ok(Err(err)) if result.is_ok() => result = Err(err),
+                Ok(_) => {}
+                Err(panic) => resume_unwind(panic),
+            }
+        }
+        result
+    }
+}
+
+impl Drop for Actors {
+    fn drop(&mut self) {
+        // Detach the workers. Waiting workers finish once the mailboxes are closed, and running
+        // scripts are interrupted.
+        self.close();
+        if !self.workers.is_empty() {
+            self.interrupts.lock().interrupt_all();
+        }
+    }
+}
+
+impl fmt::Debug for Actors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Actors")
+            .field("workers", &self.workers.len())
+            .finish()
+    }
+}
+
+struct Worker {
+    id: usize,
+    count: usize,
+    mailboxes: Arc<Mailboxes>,
+    inbox: Receiver<Envelope>,
+    outbox: Sender<(usize, ActorValue)>,
+    interrupts: Arc<Mutex<Interrupts>>,
+}
+
+impl Worker {
+    fn run(self, source: &[u8], factory: &Factory) -> Result<()> {
+        let id = self.id;
+        let lua = factory()?;
+        // Keep the handles alive while the script is running, dropping them clears the request
+        let interrupts = self.interrupts.clone();
+        interrupts.lock().register(lua.interrupt_handle());
+        let library = self.into_library(&lua)?;
+        lua.globals().raw_set("actor", library)?;
+        lua.load(source).set_name(format!("=actor[{id}]")).exec()
+    }
+
+    fn into_library(self, lua: &Lua) -> Result<Table> {
+        let Worker {
+            id,
+            count,
+            mailboxes,
+            inbox,
+            outbox,
+            interrupts: _,
+        } = self;
+
+        let library = lua.create_table()?;
+        library.raw_set("id", id)?;
+        library.raw_set("count", count)?;
+
+        let send = lua.create_function(move |_, value: ActorValue| Ok(outbox.send((id, value)).is_ok()))?;
+        library.raw_set("send", send)?;
+
+        let post = lua.create_function(move |_, (worker, value): (usize, ActorValue)| {
+            Ok(mailboxes.send(worker, (Some(id), value)).is_ok())
+        })?;
+        library.raw_set("post", post)?;
+
+        let receive = lua.create_function(move |_, timeout: Option<f64>| {
+            let envelope = match timeout {
+                Some(secs) => {
+                    let timeout = Duration::try_from_secs_f64(secs.max(0.0)).map_err(Error::runtime)?;
+                    inbox.recv_timeout(timeout).ok()
+                }
+                None => inbox.recv().ok(),
+            };
+            Ok(match envelope {
+                Some((from, value)) => (Some(value), from),
+                None => (None, None),
+            })
+        })?;
+        library.raw_set("receive", receive)?;
+
+        Ok(library)
+    }
+}
+
+#[cfg(test)]
+mod assertions {
+    use super::*;
+
+    static_assertions::assert_impl_all!(Actors: Send, Sync);
+    static_assertions::assert_impl_all!(ActorValue: Send, Sync);
+}
@@ -1,6 +1,9 @@
+use std::io;
+
 #[cfg(feature = "serde")]
 use serde::ser::{Serialize, Serializer};
 
+use crate::error::{Error, Result};
 use crate::types::ValueRef;
 
 /// A Luau buffer type.
@@ -12,6 +15,28 @@ use crate::types::ValueRef;
 #[derive(Clone, Debug, PartialEq)]
 pub struct Buffer(pub(crate) ValueRef);
 
+macro_rules! buffer_read_write {
+    ($($ty:ty => $read:ident, $write:ident, $from_bytes:ident, $to_bytes:ident;)*) => {
+        $(
+            #[doc = concat!("Reads a `", stringify!($ty), "` from the buffer at the given offset.")]
+            ///
+            /// Offset is 0-based. Returns an error if the read is out of bounds.
+            #[inline]
+            pub fn $read(&self, offset: usize) -> Result<$ty> {
+                Ok(<$ty>::$from_bytes(self.try_read_bytes(offset)?))
+            }
+
+            #[doc = concat!("Writes a `", stringify!($ty), "` to the buffer at the given offset.")]
+            ///
+            /// Offset is 0-based. Returns an error if the write is out of bounds.
+            #[inline]
+            pub fn $write(&self, offset: usize, value: $ty) -> Result<()> {
+                self.try_write_bytes(offset, &value.$to_bytes())
+            }
+        )*
+    };
+}
+
 #[cfg_attr(not(feature = "luau"), allow(unused))]
 impl Buffer {
     /// Copies the buffer data into a new `Vec<u8>`.
@@ -32,6 +57,11 @@ impl Buffer {
     /// Reads given number of bytes from the buffer at the given offset.
     ///
     /// Offset is 0-based.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the read is out of bounds. See [`Buffer::try_read_bytes`] for a non-panicking
+    /// version.
     #[track_caller]
     pub fn read_bytes<const N: usize>(&self, offset: usize) -> [u8; N] {
         let data = unsafe { self.as_slice() };
@@ -43,20 +73,108 @@ impl Buffer {
     /// Writes given bytes to the buffer at the given offset.
     ///
     /// Offset is 0-based.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the write is out of bounds. See [`Buffer::try_write_bytes`] for a non-panicking
+    /// version.
     #[track_caller]
     pub fn write_bytes(&self, offset: usize, bytes: &[u8]) {
-        let data = unsafe {
-            let (buf, size) = self.as_raw_parts();
-            std::slice::from_raw_parts_mut(buf, size)
-        };
+        let data = unsafe { self.as_slice_mut() };
         data[offset..offset + bytes.len()].copy_from_slice(bytes);
     }
 
+    /// Reads given number of bytes from the buffer at the given offset.
+    ///
+    /// Offset is 0-based. Returns an error if the read is out of bounds.
+    pub fn try_read_bytes<const N: usize>(&self, offset: usize) -> Result<[u8; N]> {
+        unsafe {
+            self.with_bytes(|data| {
+                let range = check_range(offset, N, data.len())?;
+                let mut bytes = [0u8; N];
+                bytes.copy_from_slice(&data[range]);
+                Ok(bytes)
+            })
+        }
+    }
+
+    /// Writes given bytes to the buffer at the given offset.
+    ///
+    /// Offset is 0-based. Returns an error if the write is out of bounds.
+    pub fn try_write_bytes(&self, offset: usize, bytes: &[u8]) -> Result<()> {
+        unsafe {
+            self.with_bytes_mut(|data| {
+                let range = check_range(offset, bytes.len(), data.len())?;
+                data[range].copy_from_slice(bytes);
+                Ok(())
+            })
+        }
+    }
+
+    buffer_read_write! {
+        u8 => read_u8, write_u8, from_le_bytes, to_le_bytes;
+        i8 => read_i8, write_i8, from_le_bytes, to_le_bytes;
+        u16 => read_u16_le, write_u16_le, from_le_bytes, to_le_bytes;
+        u16 => read_u16_be, write_u16_be, from_be_bytes, to_be_bytes;
+        i16 => read_i16_le, write_i16_le, from_le_bytes, to_le_bytes;
+        i16 => read_i16_be, write_i16_be, from_be_bytes, to_be_bytes;
+        u32 => read_u32_le, write_u32_le, from_le_bytes, to_le_bytes;
+        u32 => read_u32_be, write_u32_be, from_be_bytes, to_be_bytes;
+        i32 => read_i32_le, write_i32_le, from_le_bytes, to_le_bytes;
+        i32 => read_i32_be, write_i32_be, from_be_bytes, to_be_bytes;
+        u64 => read_u64_le, write_u64_le, from_le_bytes, to_le_bytes;
+        u64 => read_u64_be, write_u64_be, from_be_bytes, to_be_bytes;
+        i64 => read_i64_le, write_i64_le, from_le_bytes, to_le_bytes;
+        i64 => read_i64_be, write_i64_be, from_be_bytes, to_be_bytes;
+        f32 => read_f32_le, write_f32_le, from_le_bytes, to_le_bytes;
+        f32 => read_f32_be, write_f32_be, from_be_bytes, to_be_bytes;
+        f64 => read_f64_le, write_f64_le, from_le_bytes, to_le_bytes;
+        f64 => read_f64_be, write_f64_be, from_be_bytes, to_be_bytes;
+    }
+
+    /// Calls the closure with the buffer data as a byte slice, without copying it.
+    ///
+    /// The Lua state is locked for the duration of the call.
+    ///
+    /// # Safety
+    ///
+    /// The closure must not modify the buffer, neither using [`Buffer`] methods nor by running Lua
+    /// code, while the slice is alive.
+    pub unsafe fn with_bytes<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R {
+        let _lua = self.0.lua.lock();
+        f(self.as_slice())
+    }
+
+    /// Calls the closure with the buffer data as a mutable byte slice, without copying it.
+    ///
+    /// The Lua state is locked for the duration of the call.
+    ///
+    /// # Safety
+    ///
+    /// The closure must not access the buffer in any other way (including nested calls of this
+    /// method), neither using [`Buffer`] methods nor by running Lua code, while the slice is alive.
+    pub unsafe fn with_bytes_mut<R>(&self, f: impl FnOnce(&mut [u8]) -> R) -> R {
+        let _lua = self.0.lua.lock();
+        f(self.as_slice_mut())
+    }
+
+    /// Returns a [`BufferCursor`] that implements [`std::io::Read`], [`std::io::Write`] and
+    /// [`std::io::Seek`] over this buffer.
+    pub fn cursor(self) -> BufferCursor {
+        BufferCursor::new(self)
+    }
+
     pub(crate) unsafe fn as_slice(&self) -> &[u8] {
         let (buf, size) = self.as_raw_parts();
         std::slice::from_raw_parts(buf, size)
     }
 
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn as_slice_mut(&self) -> &mut [u8] {
+        let (buf, size) = self.as_raw_parts();
+        std::slice::from_raw_parts_mut(buf, size)
+    }
+
     #[cfg(feature = "luau")]
     unsafe fn as_raw_parts(&self) -> (*mut u8, usize) {
         let lua = self.0.lua.lock();
@@ -72,6 +190,121 @@ impl Buffer {
     }
 }
 
+#[cfg_attr(not(feature = "luau"), allow(unused))]
+fn check_range(offset: usize, len: usize, size: usize) -> Result<std::ops::Range<usize>> {
+    match offset.checked_add(len) {
+        Some(end) if end <= size => Ok(offset..end),
+        _ => Err(Error::runtime(format!(
+            "buffer access out of bounds (offset {offset}, length {len}, buffer size {size})"
+        ))),
+    }
+}
+
+/// A cursor over a Luau [`Buffer`].
+///
+/// Implements [`std::io::Read`], [`std::io::Write`] and [`std::io::Seek`] traits.
+/// Luau buffers have a fixed size, so writing past the end of the buffer writes nothing.
+///
+/// This struct is created by the [`Buffer::cursor`] method.
+#[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
+#[cfg_attr(not(feature = "luau"), allow(unused))]
+#[derive(Clone, Debug)]
+pub struct BufferCursor {
+    buffer: Buffer,
+    pos: u64,
+}
+
+#[cfg_attr(not(feature = "luau"), allow(unused))]
+impl BufferCursor {
+    /// Creates a new cursor positioned at the start of the buffer.
+    pub fn new(buffer: Buffer) -> Self {
+        BufferCursor { buffer, pos: 0 }
+    }
+
+    /// Returns the current position of the cursor.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Sets the position of the cursor.
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+
+    /// Returns a reference to the underlying buffer.
+    pub fn get_ref(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Consumes the cursor, returning the underlying buffer.
+    pub fn into_inner(self) -> Buffer {
+        self.buffer
+    }
+
+    // Returns the cursor position clamped to the given buffer size
+    fn offset(&self, size: usize) -> usize {
+        usize::try_from(self.pos).unwrap_or(usize::MAX).min(size)
+    }
+}
+
+impl io::Read for BufferCursor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = unsafe {
+            self.buffer.with_bytes(|data| {
+                let remaining = &data[self.offset(data.len())..];
+                let n = remaining.len().min(buf.len());
+                buf[..n].copy_from_slice(&remaining[..n]);
+                n
+            })
+        };
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl io::Write for BufferCursor {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = unsafe {
+            self.buffer.with_bytes_mut(|data| {
+                let offset = self.offset(data.len());
+                let remaining = &mut data[offset..];
+                let n = remaining.len().min(buf.len());
+                remaining[..n].copy_from_slice(&buf[..n]);
+                n
+            })
+        };
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl io::Seek for BufferCursor {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            io::SeekFrom::Start(n) => {
+                self.pos = n;
+                return Ok(n);
+            }
+            io::SeekFrom::End(n) => (self.buffer.len() as u64, n),
+            io::SeekFrom::Current(n) => (self.pos, n),
+        };
+        match base.checked_add_signed(offset) {
+            Some(n) => {
+                self.pos = n;
+                Ok(n)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
 #[cfg(feature = "serde")]
 impl Serialize for Buffer {
     fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
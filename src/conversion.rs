@@ -22,6 +22,13 @@ use crate::types::{Either, LightUserData, MaybeSend, RegistryKey};
 use crate::userdata::{AnyUserData, UserData};
 use crate::value::{Nil, Value};
 
+#[cfg(feature = "std-types")]
+use std::{
+    collections::VecDeque,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
 impl IntoLua for Value {
     #[inline]
     fn into_lua(self, _: &Lua) -> Result<Value> {
@@ -464,6 +471,28 @@ impl FromLua for crate::Vector {
     }
 }
 
+#[cfg(all(feature = "userdata-vector", not(feature = "luau")))]
+impl FromLua for crate::Vector {
+    #[inline]
+    fn from_lua(value: Value, _: &Lua) -> Result<Self> {
+        match value {
+            Value::UserData(ref ud) => match ud.borrow::<crate::Vector>() {
+                Ok(v) => Ok(*v),
+                Err(err) => Err(Error::FromLuaConversionError {
+                    from: "userdata",
+                    to: "vector".to_string(),
+                    message: Some(err.to_string()),
+                }),
+            },
+            _ => Err(Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: "vector".to_string(),
+                message: None,
+            }),
+        }
+    }
+}
+
 #[cfg(feature = "luau")]
 impl IntoLua for crate::Buffer {
     #[inline]
@@ -1030,6 +1059,29 @@ impl<T: FromLua> FromLua for Vec<T> {
     }
 }
 
+#[cfg(feature = "std-types")]
+impl<T: IntoLua> IntoLua for VecDeque<T> {
+    #[inline]
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        Ok(Value::Table(lua.create_sequence_from(self)?))
+    }
+}
+
+#[cfg(feature = "std-types")]
+impl<T: FromLua> FromLua for VecDeque<T> {
+    #[inline]
+    fn from_lua(value: Value, _lua: &Lua) -> Result<Self> {
+        match value {
+            Value::Table(table) => table.sequence_values().collect(),
+            _ => Err(Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: Self::type_name(),
+                message: Some("expected table".to_string()),
+            }),
+        }
+    }
+}
+
 impl<K: Eq + Hash + IntoLua, V: IntoLua, S: BuildHasher> IntoLua for HashMap<K, V, S> {
     #[inline]
     fn into_lua(self, lua: &Lua) -> Result<Value> {
@@ -1214,3 +1266,337 @@ impl<L: FromLua, R: FromLua> FromLua for Either<L, R> {
         }
     }
 }
+
+#[cfg(feature = "std-types")]
+impl IntoLua for Duration {
+    #[inline]
+    fn into_lua(self, _: &Lua) -> Result<Value> {
+        Ok(Value::Number(self.as_secs_f64()))
+    }
+}
+
+#[cfg(feature = "std-types")]
+impl FromLua for Duration {
+    fn from_lua(value: Value, lua: &Lua) -> Result<Self> {
+        let ty = value.type_name();
+        let secs = f64::from_lua(value, lua)?;
+        Duration::try_from_secs_f64(secs).map_err(|err| Error::FromLuaConversionError {
+            from: ty,
+            to: Self::type_name(),
+            message: Some(err.to_string()),
+        })
+    }
+}
+
+#[cfg(feature = "std-types")]
+impl IntoLua for SystemTime {
+    fn into_lua(self, _: &Lua) -> Result<Value> {
+        // Seconds (with fractional part) relative to the Unix epoch
+        let secs = match self.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs_f64(),
+            Err(err) => -err.duration().as_secs_f64(),
+        };
+        Ok(Value::Number(secs))
+    }
+}
+
+#[cfg(feature = "std-types")]
+impl FromLua for SystemTime {
+    fn from_lua(value: Value, lua: &Lua) -> Result<Self> {
+        let ty = value.type_name();
+        let secs = f64::from_lua(value, lua)?;
+        let time = Duration::try_from_secs_f64(secs.abs())
+            .ok()
+            .and_then(|d| match secs < 0.0 {
+                true => UNIX_EPOCH.checked_sub(d),
+                false => UNIX_EPOCH.checked_add(d),
+            });
+        time.ok_or_else(|| Error::FromLuaConversionError {
+            from: ty,
+            to: Self::type_name(),
+            message: Some("out of range".to_string()),
+        })
+    }
+}
+
+// Converts a value from its string representation
+#[cfg(any(feature = "std-types", feature = "chrono", feature = "uuid"))]
+fn from_lua_str<T>(value: Value) -> Result<T>
+where
+    T: str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let ty = value.type_name();
+    let conversion_error = |message: StdString| Error::FromLuaConversionError {
+        from: ty,
+        to: T::type_name(),
+        message: Some(message),
+    };
+    let s = match value {
+        Value::String(s) => s,
+        _ => return Err(conversion_error("expected string".to_string())),
+    };
+    let s = s.to_str().map_err(|err| conversion_error(err.to_string()))?;
+    s.parse::<T>().map_err(|err| conversion_error(err.to_string()))
+}
+
+#[cfg(any(feature = "std-types", feature = "chrono", feature = "uuid"))]
+macro_rules! lua_convert_str {
+    ($x:ty) => {
+        impl IntoLua for $x {
+            #[inline]
+            fn into_lua(self, lua: &Lua) -> Result<Value> {
+                Ok(Value::String(lua.create_string(self.to_string())?))
+            }
+        }
+
+        impl FromLua for $x {
+            #[inline]
+            fn from_lua(value: Value, _: &Lua) -> Result<Self> {
+                from_lua_str(value)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "std-types")]
+lua_convert_str!(IpAddr);
+#[cfg(feature = "std-types")]
+lua_convert_str!(Ipv4Addr);
+#[cfg(feature = "std-types")]
+lua_convert_str!(Ipv6Addr);
+#[cfg(feature = "std-types")]
+lua_convert_str!(SocketAddr);
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> IntoLua for chrono::DateTime<Tz>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    #[inline]
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        Ok(Value::String(lua.create_string(self.to_rfc3339())?))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FromLua for chrono::DateTime<chrono::FixedOffset> {
+    fn from_lua(value: Value, lua: &Lua) -> Result<Self> {
+        let ty = value.type_name();
+        let conversion_error = |message: StdString| Error::FromLuaConversionError {
+            from: ty,
+            to: Self::type_name(),
+            message: Some(message),
+        };
+        match value {
+            Value::String(s) => {
+                let s = s.to_str().map_err(|err| conversion_error(err.to_string()))?;
+                Self::parse_from_rfc3339(&s).map_err(|err| conversion_error(err.to_string()))
+            }
+            Value::Integer(_) | Value::Number(_) => {
+                // Seconds (with fractional part) relative to the Unix epoch
+                let secs = f64::from_lua(value, lua)?;
+                if !secs.is_finite() {
+                    return Err(conversion_error("expected finite number".to_string()));
+                }
+                let nanos = (secs.fract() * 1e9).round() as i64;
+                chrono::DateTime::from_timestamp(secs.trunc() as i64, 0)
+                    .and_then(|dt| dt.checked_add_signed(chrono::TimeDelta::nanoseconds(nanos)))
+                    .map(|dt| dt.fixed_offset())
+                    .ok_or_else(|| conversion_error("out of range".to_string()))
+            }
+            _ => Err(conversion_error("expected string or number".to_string())),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FromLua for chrono::DateTime<chrono::Utc> {
+    #[inline]
+    fn from_lua(value: Value, lua: &Lua) -> Result<Self> {
+        chrono::DateTime::<chrono::FixedOffset>::from_lua(value, lua).map(|dt| dt.to_utc())
+    }
+}
+
+#[cfg(feature = "chrono")]
+lua_convert_str!(chrono::NaiveDate);
+#[cfg(feature = "chrono")]
+lua_convert_str!(chrono::NaiveTime);
+#[cfg(feature = "chrono")]
+impl IntoLua for chrono::NaiveDateTime {
+    #[inline]
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        // `Display` uses a space separator which is not accepted back by `FromStr`
+        let s = self.format("%Y-%m-%dT%H:%M:%S%.f").to_string();
+        Ok(Value::String(lua.create_string(s)?))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FromLua for chrono::NaiveDateTime {
+    #[inline]
+    fn from_lua(value: Value, _: &Lua) -> Result<Self> {
+        from_lua_str(value)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl IntoLua for chrono::TimeDelta {
+    #[inline]
+    fn into_lua(self, _: &Lua) -> Result<Value> {
+        let secs = self.num_seconds() as f64 + self.subsec_nanos() as f64 / 1e9;
+        Ok(Value::Number(secs))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FromLua for chrono::TimeDelta {
+    fn from_lua(value: Value, lua: &Lua) -> Result<Self> {
+        let ty = value.type_name();
+        let secs = f64::from_lua(value, lua)?;
+        let nanos = secs * 1e9;
+        if !nanos.is_finite() || nanos.abs() >= i64::MAX as f64 {
+            return Err(Error::FromLuaConversionError {
+                from: ty,
+                to: Self::type_name(),
+                message: Some("out of range".to_string()),
+            });
+        }
+        Ok(chrono::TimeDelta::nanoseconds(nanos.round() as i64))
+    }
+}
+
+#[cfg(feature = "uuid")]
+lua_convert_str!(uuid::Uuid);
+
+#[cfg(feature = "bytes")]
+impl IntoLua for bytes::Bytes {
+    #[inline]
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        Ok(Value::String(lua.create_string(&self)?))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl FromLua for bytes::Bytes {
+    #[inline]
+    fn from_lua(value: Value, lua: &Lua) -> Result<Self> {
+        Ok(bytes::Bytes::from(Vec::from(BString::from_lua(value, lua)?)))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl IntoLua for bytes::BytesMut {
+    #[inline]
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        Ok(Value::String(lua.create_string(&self)?))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl FromLua for bytes::BytesMut {
+    #[inline]
+    fn from_lua(value: Value, lua: &Lua) -> Result<Self> {
+        Ok(bytes::BytesMut::from(BString::from_lua(value, lua)?.as_slice()))
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K: Eq + Hash + IntoLua, V: IntoLua, S: BuildHasher> IntoLua for indexmap::IndexMap<K, V, S> {
+    #[inline]
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        Ok(Value::Table(lua.create_table_from(self)?))
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V, S> FromLua for indexmap::IndexMap<K, V, S>
+where
+    K: Eq + Hash + FromLua,
+    V: FromLua,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn from_lua(value: Value, _: &Lua) -> Result<Self> {
+        if let Value::Table(table) = value {
+            table.pairs().collect()
+        } else {
+            Err(Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: Self::type_name(),
+                message: Some("expected table".to_string()),
+            })
+        }
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<T: Eq + Hash + IntoLua, S: BuildHasher> IntoLua for indexmap::IndexSet<T, S> {
+    #[inline]
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        Ok(Value::Table(
+            lua.create_table_from(self.into_iter().map(|val| (val, true)))?,
+        ))
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<T: Eq + Hash + FromLua, S: BuildHasher + Default> FromLua for indexmap::IndexSet<T, S> {
+    #[inline]
+    fn from_lua(value: Value, _: &Lua) -> Result<Self> {
+        match value {
+            Value::Table(table) if table.raw_len() > 0 => table.sequence_values().collect(),
+            Value::Table(table) => table.pairs::<T, Value>().map(|res| res.map(|(k, _)| k)).collect(),
+            _ => Err(Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: Self::type_name(),
+                message: Some("expected table".to_string()),
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> IntoLua for smallvec::SmallVec<A>
+where
+    A::Item: IntoLua,
+{
+    #[inline]
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        Ok(Value::Table(lua.create_sequence_from(self)?))
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> FromLua for smallvec::SmallVec<A>
+where
+    A::Item: FromLua,
+{
+    #[inline]
+    fn from_lua(value: Value, _: &Lua) -> Result<Self> {
+        match value {
+            Value::Table(table) => table.sequence_values().collect(),
+            _ => Err(Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: Self::type_name(),
+                message: Some("expected table".to_string()),
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl IntoLua for serde_json::Value {
+    #[inline]
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        crate::LuaSerdeExt::to_value(lua, &self)
+    }
+}
+
+#[cfg(feature = "json")]
+impl FromLua for serde_json::Value {
+    #[inline]
+    fn from_lua(value: Value, lua: &Lua) -> Result<Self> {
+        crate::LuaSerdeExt::from_value(lua, value)
+    }
+}
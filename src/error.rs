@@ -16,6 +16,12 @@ type DynStdError = dyn StdError + Send + Sync;
 type DynStdError = dyn StdError;
 
 /// Error type returned by `mlua` methods.
+///
+/// # Paths
+///
+/// Errors about a value nested inside tables (such as [`Error::SchemaError`]) carry the path to
+/// that value, written like a Lua expression: `name` for fields that are identifiers, `["key"]`
+/// for other string keys and `[n]` for other keys. Array indices are Lua (1-based) table indices.
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 pub enum Error {
@@ -180,6 +186,10 @@ pub enum Error {
     /// This error can occur only when a Rust panic resumed previously was recovered
     /// and returned again.
     PreviouslyResumedPanic,
+    /// Lua code execution was interrupted by an [`InterruptHandle`].
+    ///
+    /// [`InterruptHandle`]: crate::InterruptHandle
+    Interrupted,
     /// Serialization error.
     #[cfg(feature = "serde")]
     #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
@@ -188,6 +198,25 @@ pub enum Error {
     #[cfg(feature = "serde")]
     #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
     DeserializeError(StdString),
+    /// Deserialization error of a value nested inside tables.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    DeserializePathError {
+        /// Path to the value that failed to deserialize, for example `servers[3].ports[1]` (see
+        /// [paths](Error#paths)).
+        path: StdString,
+        /// The underlying deserialization error message.
+        message: StdString,
+    },
+    /// A table does not match a [`TableSchema`].
+    ///
+    /// [`TableSchema`]: crate::TableSchema
+    SchemaError {
+        /// Path to the offending value, for example `servers[3].port` (see [paths](Error#paths)).
+        path: StdString,
+        /// A message describing the mismatch.
+        message: StdString,
+    },
     /// A custom error.
     ///
     /// This can be used for returning user-defined errors from callbacks.
@@ -307,6 +336,7 @@ impl fmt::Display for Error {
             Error::PreviouslyResumedPanic => {
                 write!(fmt, "previously resumed panic returned again")
             }
+            Error::Interrupted => write!(fmt, "execution interrupted"),
             #[cfg(feature = "serde")]
             Error::SerializeError(err) => {
                 write!(fmt, "serialize error: {err}")
@@ -315,6 +345,11 @@ impl fmt::Display for Error {
             Error::DeserializeError(err) => {
                 write!(fmt, "deserialize error: {err}")
             },
+            #[cfg(feature = "serde")]
+            Error::DeserializePathError { path, message } => {
+                write!(fmt, "deserialize error at `{path}`: {message}")
+            },
+            Error::SchemaError { path, message } => write!(fmt, "schema error at `{path}`: {message}"),
             Error::ExternalError(err) => err.fmt(fmt),
             Error::WithContext { context, cause } => {
                 writeln!(fmt, "{context}")?;
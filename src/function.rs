@@ -7,6 +7,7 @@ use crate::error::{Error, Result};
 use crate::state::util::get_next_spot;
 use crate::state::Lua;
 use crate::table::Table;
+use crate::thread::Generator;
 use crate::traits::{FromLuaMulti, IntoLua, IntoLuaMulti, LuaNativeFn, LuaNativeFnMut};
 use crate::types::{Callback, LuaType, MaybeSend, ValueRef};
 use crate::util::{
@@ -290,6 +291,15 @@ impl Function {
         }
     }
 
+    /// Creates a new thread from this function and returns a [`Generator`] that iterates over
+    /// the values yielded by it.
+    ///
+    /// See [`Generator`] for details.
+    pub fn as_generator<T: FromLuaMulti>(&self) -> Result<Generator<T>> {
+        let lua = self.0.lua.lock();
+        Ok(unsafe { lua.create_thread(self) }?.into_generator())
+    }
+
     /// Returns information about the function.
     ///
     /// Corresponds to the `>Sn` what mask for [`lua_getinfo`] when applied to the function.
@@ -7,7 +7,7 @@ use std::os::raw::c_int;
 
 use ffi::lua_Debug;
 
-use crate::state::RawLua;
+use crate::state::{LuaGuard, RawLua};
 use crate::types::ReentrantMutexGuard;
 use crate::util::{linenumber_to_usize, ptr_to_lossy_str, ptr_to_str};
 
@@ -22,6 +22,8 @@ use crate::util::{linenumber_to_usize, ptr_to_lossy_str, ptr_to_str};
 /// [`Lua::set_hook`]: crate::Lua::set_hook
 pub struct Debug<'a> {
     lua: EitherLua<'a>,
+    // State of the inspected thread (if not the current one)
+    thread: Option<*mut ffi::lua_State>,
     ar: ActivationRecord,
     #[cfg(feature = "luau")]
     level: c_int,
@@ -29,6 +31,7 @@ pub struct Debug<'a> {
 
 enum EitherLua<'a> {
     Owned(ReentrantMutexGuard<'a, RawLua>),
+    Shared(LuaGuard),
     #[cfg(not(feature = "luau"))]
     Borrowed(&'a RawLua),
 }
@@ -39,6 +42,7 @@ impl Deref for EitherLua<'_> {
     fn deref(&self) -> &Self::Target {
         match self {
             EitherLua::Owned(guard) => guard,
+            EitherLua::Shared(guard) => guard,
             #[cfg(not(feature = "luau"))]
             EitherLua::Borrowed(lua) => lua,
         }
@@ -51,6 +55,7 @@ impl<'a> Debug<'a> {
     pub(crate) fn new(lua: &'a RawLua, ar: *mut lua_Debug) -> Self {
         Debug {
             lua: EitherLua::Borrowed(lua),
+            thread: None,
             ar: ActivationRecord::Borrowed(ar),
         }
     }
@@ -58,12 +63,34 @@ impl<'a> Debug<'a> {
     pub(crate) fn new_owned(guard: ReentrantMutexGuard<'a, RawLua>, _level: c_int, ar: lua_Debug) -> Self {
         Debug {
             lua: EitherLua::Owned(guard),
+            thread: None,
             ar: ActivationRecord::Owned(UnsafeCell::new(ar)),
             #[cfg(feature = "luau")]
             level: _level,
         }
     }
 
+    // Used to inspect a stack of another (suspended) thread
+    pub(crate) fn new_thread(
+        guard: LuaGuard,
+        state: *mut ffi::lua_State,
+        _level: c_int,
+        ar: lua_Debug,
+    ) -> Self {
+        Debug {
+            lua: EitherLua::Shared(guard),
+            thread: Some(state),
+            ar: ActivationRecord::Owned(UnsafeCell::new(ar)),
+            #[cfg(feature = "luau")]
+            level: _level,
+        }
+    }
+
+    #[inline]
+    fn state(&self) -> *mut ffi::lua_State {
+        self.thread.unwrap_or_else(|| self.lua.state())
+    }
+
     /// Returns the specific event that triggered the hook.
     ///
     /// For [Lua 5.1] [`DebugEvent::TailCall`] is used for return events to indicate a return
@@ -90,12 +117,12 @@ impl<'a> Debug<'a> {
         unsafe {
             #[cfg(not(feature = "luau"))]
             mlua_assert!(
-                ffi::lua_getinfo(self.lua.state(), cstr!("n"), self.ar.get()) != 0,
+                ffi::lua_getinfo(self.state(), cstr!("n"), self.ar.get()) != 0,
                 "lua_getinfo failed with `n`"
             );
             #[cfg(feature = "luau")]
             mlua_assert!(
-                ffi::lua_getinfo(self.lua.state(), self.level, cstr!("n"), self.ar.get()) != 0,
+                ffi::lua_getinfo(self.state(), self.level, cstr!("n"), self.ar.get()) != 0,
                 "lua_getinfo failed with `n`"
             );
 
@@ -117,12 +144,12 @@ impl<'a> Debug<'a> {
         unsafe {
             #[cfg(not(feature = "luau"))]
             mlua_assert!(
-                ffi::lua_getinfo(self.lua.state(), cstr!("S"), self.ar.get()) != 0,
+                ffi::lua_getinfo(self.state(), cstr!("S"), self.ar.get()) != 0,
                 "lua_getinfo failed with `S`"
             );
             #[cfg(feature = "luau")]
             mlua_assert!(
-                ffi::lua_getinfo(self.lua.state(), self.level, cstr!("s"), self.ar.get()) != 0,
+                ffi::lua_getinfo(self.state(), self.level, cstr!("s"), self.ar.get()) != 0,
                 "lua_getinfo failed with `s`"
             );
 
@@ -147,12 +174,12 @@ impl<'a> Debug<'a> {
         unsafe {
             #[cfg(not(feature = "luau"))]
             mlua_assert!(
-                ffi::lua_getinfo(self.lua.state(), cstr!("l"), self.ar.get()) != 0,
+                ffi::lua_getinfo(self.state(), cstr!("l"), self.ar.get()) != 0,
                 "lua_getinfo failed with `l`"
             );
             #[cfg(feature = "luau")]
             mlua_assert!(
-                ffi::lua_getinfo(self.lua.state(), self.level, cstr!("l"), self.ar.get()) != 0,
+                ffi::lua_getinfo(self.state(), self.level, cstr!("l"), self.ar.get()) != 0,
                 "lua_getinfo failed with `l`"
             );
 
@@ -167,7 +194,7 @@ impl<'a> Debug<'a> {
     pub fn is_tail_call(&self) -> bool {
         unsafe {
             mlua_assert!(
-                ffi::lua_getinfo(self.lua.state(), cstr!("t"), self.ar.get()) != 0,
+                ffi::lua_getinfo(self.state(), cstr!("t"), self.ar.get()) != 0,
                 "lua_getinfo failed with `t`"
             );
             (*self.ar.get()).currentline != 0
@@ -179,12 +206,12 @@ impl<'a> Debug<'a> {
         unsafe {
             #[cfg(not(feature = "luau"))]
             mlua_assert!(
-                ffi::lua_getinfo(self.lua.state(), cstr!("u"), self.ar.get()) != 0,
+                ffi::lua_getinfo(self.state(), cstr!("u"), self.ar.get()) != 0,
                 "lua_getinfo failed with `u`"
             );
             #[cfg(feature = "luau")]
             mlua_assert!(
-                ffi::lua_getinfo(self.lua.state(), self.level, cstr!("au"), self.ar.get()) != 0,
+                ffi::lua_getinfo(self.state(), self.level, cstr!("au"), self.ar.get()) != 0,
                 "lua_getinfo failed with `au`"
             );
 
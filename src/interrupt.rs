@@ -0,0 +1,181 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+
+use crate::error::{Error, Result};
+use crate::types::RegistryKey;
+
+/// A handle to interrupt running Lua code from any thread.
+///
+/// The handle is created by [`Lua::interrupt_handle`] or [`Thread::interrupt_handle`] and does
+/// not hold the Lua lock, so it can be used (for example by a watchdog thread) while the Lua
+/// state is busy running a script.
+///
+/// Once [`InterruptHandle::interrupt`] is called, the running Lua code fails with
+/// [`Error::Interrupted`] the next time the VM checks for interrupts (an interrupt callback on
+/// Luau, an instruction count hook on other Lua versions). The request is sticky: every
+/// subsequent check fails as well (so the error cannot be swallowed by `pcall`) until
+/// [`InterruptHandle::reset`] is called or all clones of the handle are dropped.
+///
+/// A handle created by [`Thread::interrupt_handle`] keeps the thread alive (it cannot be garbage
+/// collected while the handle exists), and is detached from the thread when the thread is reset
+/// with [`Thread::reset`]. A detached handle no longer has any effect, so it cannot interrupt code
+/// that runs in the thread after it was reused (for example by a [`ThreadPool`]).
+///
+/// On Lua versions other than Luau, interrupts are checked by an instruction count hook, which is
+/// installed on the main and current threads when the handle is created, and is inherited by
+/// threads created afterwards. Setting a custom hook with [`Lua::set_hook`] replaces the interrupt
+/// hook, in which case interrupts are checked only when the custom hook is triggered.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use mlua::{Error, Lua, Result};
+/// # fn main() -> Result<()> {
+/// let lua = Lua::new();
+/// let handle = lua.interrupt_handle();
+///
+/// std::thread::spawn(move || {
+///     std::thread::sleep(Duration::from_millis(50));
+///     handle.interrupt();
+/// });
+///
+/// let result = lua.load("while true do end").exec();
+/// assert!(matches!(result, Err(Error::Interrupted)));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Lua::interrupt_handle`]: crate::Lua::interrupt_handle
+/// [`Thread::interrupt_handle`]: crate::Thread::interrupt_handle
+/// [`Thread::reset`]: crate::Thread::reset
+/// [`ThreadPool`]: crate::ThreadPool
+/// [`Lua::set_hook`]: crate::Lua::set_hook
+#[derive(Clone)]
+pub struct InterruptHandle(Arc<InterruptFlag>);
+
+pub(crate) struct InterruptFlag {
+    // Pointer to the target thread state (`0` targets all threads)
+    target: usize,
+    // Reference to the target thread, so its address cannot be reused while the handle is alive
+    _pin: Option<RegistryKey>,
+    // Set when the target thread is reset
+    detached: AtomicBool,
+    interrupted: AtomicBool,
+    // Number of interrupted handles (shared with `Interrupts`)
+    pending: Arc<AtomicUsize>,
+}
+
+impl Drop for InterruptFlag {
+    fn drop(&mut self) {
+        if *self.interrupted.get_mut() {
+            self.pending.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+}
+
+impl InterruptHandle {
+    /// Requests the running Lua code to stop with [`Error::Interrupted`].
+    ///
+    /// Has no effect if the handle was detached from its thread.
+    pub fn interrupt(&self) {
+        if self.0.detached.load(Ordering::Acquire) {
+            return;
+        }
+        if !self.0.interrupted.swap(true, Ordering::AcqRel) {
+            self.0.pending.fetch_add(1, Ordering::AcqRel);
+        }
+    }
+
+    /// Clears the interrupt request, allowing Lua code to run again.
+    pub fn reset(&self) {
+        if self.0.interrupted.swap(false, Ordering::AcqRel) {
+            self.0.pending.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    /// Returns `true` if an interrupt was requested and not yet reset.
+    pub fn is_interrupted(&self) -> bool {
+        self.0.interrupted.load(Ordering::Acquire)
+    }
+}
+
+impl fmt::Debug for InterruptHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InterruptHandle")
+            .field("interrupted", &self.is_interrupted())
+            .finish()
+    }
+}
+
+/// Interrupt handles registered in a Lua state.
+#[derive(Default)]
+pub(crate) struct Interrupts {
+    pending: Arc<AtomicUsize>,
+    flags: Vec<Weak<InterruptFlag>>,
+}
+
+impl Interrupts {
+    pub(crate) fn new_handle(
+        &mut self,
+        target: *mut ffi::lua_State,
+        pin: Option<RegistryKey>,
+    ) -> InterruptHandle {
+        self.flags.retain(|flag| flag.strong_count() > 0);
+        let flag = Arc::new(InterruptFlag {
+            target: target as usize,
+            _pin: pin,
+            detached: AtomicBool::new(false),
+            interrupted: AtomicBool::new(false),
+            pending: self.pending.clone(),
+        });
+        self.flags.push(Arc::downgrade(&flag));
+        InterruptHandle(flag)
+    }
+
+    /// Detaches all handles targeting the given thread and clears their requests.
+    pub(crate) fn detach(&mut self, state: *mut ffi::lua_State) {
+        for flag in self.flags.iter().filter_map(Weak::upgrade) {
+            if flag.target == state as usize {
+                flag.detached.store(true, Ordering::Release);
+                InterruptHandle(flag).reset();
+            }
+        }
+    }
+
+    /// Returns `true` if there is at least one live handle.
+    pub(crate) fn has_handles(&mut self) -> bool {
+        self.flags.retain(|flag| flag.strong_count() > 0);
+        !self.flags.is_empty()
+    }
+
+    /// Returns `true` if any handle has requested an interrupt.
+    #[inline(always)]
+    pub(crate) fn is_pending(&self) -> bool {
+        self.pending.load(Ordering::Acquire) > 0
+    }
+
+    /// Checks whether the given thread is interrupted.
+    pub(crate) fn check(&self, state: *mut ffi::lua_State) -> Result<()> {
+        if !self.is_pending() {
+            return Ok(());
+        }
+        let interrupted = self.flags.iter().filter_map(Weak::upgrade).any(|flag| {
+            flag.interrupted.load(Ordering::Acquire)
+                && !flag.detached.load(Ordering::Acquire)
+                && (flag.target == 0 || flag.target == state as usize)
+        });
+        match interrupted {
+            true => Err(Error::Interrupted),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod assertions {
+    use super::*;
+
+    static_assertions::assert_impl_all!(InterruptHandle: Send, Sync);
+}
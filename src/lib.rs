@@ -64,16 +64,22 @@
 #[macro_use]
 mod macros;
 
+#[cfg(feature = "actor")]
+mod actor;
 mod buffer;
 mod chunk;
 mod conversion;
 mod error;
 mod function;
 mod hook;
+mod interrupt;
 #[cfg(any(feature = "luau", doc))]
 mod luau;
 mod memory;
 mod multi;
+mod pool;
+#[cfg(feature = "scheduler")]
+mod scheduler;
 mod state;
 mod stdlib;
 mod string;
@@ -95,12 +101,18 @@ pub use crate::chunk::{AsChunk, Chunk, ChunkMode};
 pub use crate::error::{Error, ErrorContext, ExternalError, ExternalResult, Result};
 pub use crate::function::{Function, FunctionInfo};
 pub use crate::hook::{Debug, DebugEvent, DebugNames, DebugSource, DebugStack};
+pub use crate::interrupt::InterruptHandle;
 pub use crate::multi::{MultiValue, Variadic};
+pub use crate::pool::{LuaPool, LuaPoolOptions, LuaPoolStats, PooledLua, ResetStrategy};
 pub use crate::state::{GCMode, Lua, LuaOptions, WeakLua};
 pub use crate::stdlib::StdLib;
-pub use crate::string::{BorrowedBytes, BorrowedStr, String};
-pub use crate::table::{Table, TablePairs, TableSequence};
-pub use crate::thread::{ContinuationStatus, Thread, ThreadStatus};
+pub use crate::string::{BorrowedBytes, BorrowedStr, LuaBytes, String, StringBuilder};
+pub use crate::table::{
+    FieldType, Table, TableCursor, TablePairs, TableSchema, TableSequence, Typed, TypedTable,
+};
+pub use crate::thread::{
+    ContinuationStatus, Generator, PooledThread, Thread, ThreadPool, ThreadPoolStats, ThreadStatus,
+};
 pub use crate::traits::{
     FromLua, FromLuaMulti, IntoLua, IntoLuaMulti, LuaNativeFn, LuaNativeFnMut, ObjectLike,
 };
@@ -116,16 +128,27 @@ pub use crate::value::{Nil, Value};
 #[cfg(not(feature = "luau"))]
 pub use crate::hook::HookTriggers;
 
+#[cfg(feature = "scheduler")]
+#[cfg_attr(docsrs, doc(cfg(feature = "scheduler")))]
+pub use crate::scheduler::Scheduler;
+
+#[cfg(feature = "actor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "actor")))]
+pub use crate::actor::{ActorValue, Actors};
+
 #[cfg(any(feature = "luau", doc))]
 #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
 pub use crate::{
-    buffer::Buffer,
+    buffer::{Buffer, BufferCursor},
     chunk::{CompileConstant, Compiler},
     function::CoverageInfo,
     luau::{NavigateError, Require, TextRequirer},
-    vector::Vector,
 };
 
+#[cfg(any(feature = "luau", feature = "userdata-vector", doc))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "luau", feature = "userdata-vector"))))]
+pub use crate::vector::Vector;
+
 #[cfg(feature = "serde")]
 #[doc(inline)]
 pub use crate::serde::{de::Options as DeserializeOptions, ser::Options as SerializeOptions, LuaSerdeExt};
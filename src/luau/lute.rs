@@ -1,7 +1,12 @@
 use crate::error::{Error, Result};
 use crate::state::RawLua;
-use crate::{FromLuaMulti, Function, Lua, Table, Thread, WeakLua};
+use crate::{FromLuaMulti, Function, Lua, MultiValue, Table, Thread, Value, WeakLua};
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+// How long `Lute::run_until_complete` sleeps when the scheduler is waiting for timers or I/O
+const LUTE_PENDING_SLEEP: Duration = Duration::from_millis(1);
 
 /// Flags describing the set of lute standard libraries to load.
 #[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -30,6 +35,63 @@ impl LuteStdLib {
     }
 }
 
+/// Policy describing which lute standard libraries a VM can access.
+///
+/// A policy can be applied to any VM with [`Lute::apply_policy`] (for example from a runtime
+/// initter, to choose a policy per child VM), or to all child VMs with
+/// [`Lute::set_child_vm_policy`].
+///
+/// Libraries are exposed as globals named after the library (`fs`, `task`, `time`, ...).
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct LuteVmPolicy {
+    /// Standard libraries to expose.
+    ///
+    /// Default: [`LuteStdLib::NONE`]
+    pub stdlib: LuteStdLib,
+
+    /// Directory the ``fs`` and ``vm`` libraries are scoped to.
+    ///
+    /// Paths passed to ``fs`` functions and to ``vm.create`` are resolved relative to this
+    /// directory (following symlinks), and access outside of it raises an error. Functions that
+    /// are not known to be safe to scope are not exposed.
+    ///
+    /// Paths are checked when a function is called, so this does not protect against the
+    /// filesystem being changed concurrently (e.g. a symlink being swapped by another process)
+    /// between the check and the access.
+    ///
+    /// The ``process`` library cannot be scoped, as processes can access any path. Applying a
+    /// policy that exposes it together with this option returns an error.
+    ///
+    /// Default: **none** (unrestricted)
+    pub fs_root: Option<PathBuf>,
+}
+
+impl Default for LuteVmPolicy {
+    fn default() -> Self {
+        LuteVmPolicy::new(LuteStdLib::NONE)
+    }
+}
+
+impl LuteVmPolicy {
+    /// Returns a new policy exposing the given standard libraries.
+    pub const fn new(stdlib: LuteStdLib) -> Self {
+        LuteVmPolicy {
+            stdlib,
+            fs_root: None,
+        }
+    }
+
+    /// Sets [`fs_root`] option.
+    ///
+    /// [`fs_root`]: #structfield.fs_root
+    #[must_use]
+    pub fn fs_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.fs_root = Some(root.into());
+        self
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum LuteChildVmType {
     /// Child VM used for running Lua code
@@ -67,6 +129,34 @@ impl LuteSchedulerRunOnceResult {
     }
 }
 
+/// Result of polling the lute scheduler with [`Lute::poll`].
+#[derive(Debug, PartialEq)]
+pub enum LuteSchedulerPoll {
+    /// A thread was resumed by the scheduler.
+    ///
+    /// If the thread has finished, its results can be extracted with [`Thread::pop_results`].
+    Ready(Thread),
+    /// The scheduler has pending work (such as timers or I/O) but nothing was ready to run.
+    ///
+    /// The caller should poll again later. Lute does not expose when its next timer is due, so no
+    /// deadline is provided.
+    Pending,
+    /// The scheduler has no work left.
+    Idle,
+}
+
+impl LuteSchedulerPoll {
+    /// Returns if the poll resumed a thread.
+    pub fn is_ready(&self) -> bool {
+        matches!(self, LuteSchedulerPoll::Ready(_))
+    }
+
+    /// Returns if the scheduler has no work left.
+    pub fn is_idle(&self) -> bool {
+        matches!(self, LuteSchedulerPoll::Idle)
+    }
+}
+
 impl BitAnd for LuteStdLib {
     type Output = Self;
     fn bitand(self, rhs: Self) -> Self::Output {
@@ -207,6 +297,73 @@ impl Lute {
         Ok(())
     }
 
+    /// Sets the policy applied to every child VM created by the lute runtime.
+    ///
+    /// The policy is applied before the runtime initter (see [`Lute::set_runtime_initter`]) is
+    /// called, so the initter can expose additional libraries to specific child VMs with
+    /// [`Lute::apply_policy`]. Data copy VMs are not affected.
+    pub fn set_child_vm_policy(&self, policy: Option<LuteVmPolicy>) -> Result<()> {
+        let Some(lua) = self.0.try_upgrade() else {
+            return Err(Error::RuntimeError("Lua VM not open".into()));
+        };
+
+        let lua = lua.lock();
+        lua.set_lute_child_policy(policy);
+        Ok(())
+    }
+
+    /// Loads the standard libraries allowed by `policy` into the current Lua state and sets them
+    /// as globals.
+    ///
+    /// If [`LuteVmPolicy::fs_root`] is set, the ``fs`` and ``vm`` libraries are replaced by
+    /// wrappers scoped to this directory.
+    pub fn apply_policy(&self, policy: &LuteVmPolicy) -> Result<()> {
+        let Some(lua) = self.0.try_upgrade() else {
+            return Err(Error::RuntimeError("Lua VM not open".into()));
+        };
+        if policy.fs_root.is_some() && policy.stdlib.contains(LuteStdLib::PROCESS) {
+            return Err(Error::RuntimeError(
+                "process library cannot be exposed in a VM scoped to a directory".into(),
+            ));
+        }
+
+        lua.lock().load_lute_stdlib(policy.stdlib)?;
+        let handle = self
+            .handle()?
+            .ok_or_else(|| Error::RuntimeError("Lute runtime is not loaded".into()))?;
+        let root = match &policy.fs_root {
+            Some(root) => Some(root.canonicalize().map_err(Error::external)?),
+            None => None,
+        };
+
+        let libs = [
+            #[cfg(feature = "luau-lute-crypto")]
+            ("crypto", LuteStdLib::CRYPTO, handle.crypto),
+            ("fs", LuteStdLib::FS, handle.fs),
+            ("luau", LuteStdLib::LUAU, handle.luau),
+            #[cfg(feature = "luau-lute-net")]
+            ("net", LuteStdLib::NET, handle.net),
+            ("process", LuteStdLib::PROCESS, handle.process),
+            ("task", LuteStdLib::TASK, handle.task),
+            ("vm", LuteStdLib::VM, handle.vm),
+            ("system", LuteStdLib::SYSTEM, handle.system),
+            ("time", LuteStdLib::TIME, handle.time),
+        ];
+
+        let globals = lua.globals();
+        for (name, flag, lib) in libs {
+            let Some(lib) = lib.filter(|_| policy.stdlib.contains(flag)) else {
+                continue;
+            };
+            let lib = match &root {
+                Some(root) if SCOPED_LIBRARIES.contains(&name) => scope_library(&lua, name, &lib, root)?,
+                _ => lib,
+            };
+            globals.raw_set(name, lib)?;
+        }
+        Ok(())
+    }
+
     /// Returns if the lute scheduler has work to do
     pub fn has_work(&self) -> Result<bool> {
         let Some(lua) = self.0.try_upgrade() else {
@@ -319,6 +476,99 @@ impl Lute {
         lua.lute_run_once()
     }
 
+    /// Polls the lute scheduler without blocking.
+    ///
+    /// This runs at most one iteration of the scheduler, which makes it suitable for driving lute
+    /// from an existing event loop. Errors raised by scheduled tasks are returned as `Err`.
+    ///
+    /// Lute does not expose when its next timer is due, so [`LuteSchedulerPoll::Pending`] carries
+    /// no deadline: the caller has to poll again after a short delay of its choice (or on the next
+    /// tick of its event loop).
+    pub fn poll(&self) -> Result<LuteSchedulerPoll> {
+        let Some(lua) = self.0.try_upgrade() else {
+            return Err(Error::RuntimeError("Lua VM not open".into()));
+        };
+
+        let lua = lua.lock();
+        if !lua.has_lute_work()? {
+            return Ok(LuteSchedulerPoll::Idle);
+        }
+        match lua.lute_run_once()? {
+            LuteSchedulerRunOnceResult::Success(thread) => Ok(LuteSchedulerPoll::Ready(thread)),
+            LuteSchedulerRunOnceResult::Empty => Ok(LuteSchedulerPoll::Pending),
+        }
+    }
+
+    /// Runs the lute scheduler until `thread` has finished, and returns its results.
+    ///
+    /// If the thread has not been started yet, it is resumed first (without arguments).
+    ///
+    /// Errors raised by the thread are returned as `Err`. Other tasks run by the scheduler in the
+    /// meantime do not abort the wait: their results are discarded, and their errors are
+    /// collected and can be retrieved with [`Lute::take_task_errors`].
+    ///
+    /// # Limitations
+    ///
+    /// Lute does not expose when its next timer is due, so while the scheduler is waiting for
+    /// timers or I/O this method blocks the current OS thread in a polling loop, sleeping for a
+    /// short fixed interval (1ms) between iterations. This means up to a thousand wakeups per
+    /// second for as long as the thread waits (e.g. on a long `task.wait`), which keeps a CPU core
+    /// slightly busy. Use [`Lute::poll`] to drive the scheduler from an event loop instead.
+    pub fn run_until_complete<R>(&self, thread: &Thread) -> Result<R>
+    where
+        R: FromLuaMulti,
+    {
+        let Some(lua) = self.0.try_upgrade() else {
+            return Err(Error::RuntimeError("Lua VM not open".into()));
+        };
+
+        if thread.is_new() {
+            let results = thread.resume::<MultiValue>(())?;
+            if !thread.is_yielded() {
+                return R::from_lua_multi(results, &lua);
+            }
+        }
+
+        loop {
+            let result = {
+                let lua = lua.lock();
+                if !lua.has_lute_work()? {
+                    return Err(Error::RuntimeError(
+                        "thread is suspended but the lute scheduler has no work left".into(),
+                    ));
+                }
+                lua.lute_run_once_traced()
+            };
+            match result {
+                Ok(LuteSchedulerRunOnceResult::Success(ready)) if ready.is_yielded() => {}
+                Ok(LuteSchedulerRunOnceResult::Success(ready)) if ready == *thread => {
+                    return ready.pop_results();
+                }
+                Ok(LuteSchedulerRunOnceResult::Success(ready)) => {
+                    // Discard results of unrelated tasks
+                    let _ = ready.pop_results::<()>();
+                }
+                Ok(LuteSchedulerRunOnceResult::Empty) => std::thread::sleep(LUTE_PENDING_SLEEP),
+                Err((err, state)) if state.is_null() || state == thread.1 => return Err(err),
+                // Errors of unrelated tasks do not affect the target thread
+                Err((err, _)) => lua.lock().push_lute_task_error(err),
+            }
+        }
+    }
+
+    /// Returns (and clears) the errors of tasks that failed while [`Lute::run_until_complete`]
+    /// was waiting for another thread.
+    ///
+    /// Errors are kept until taken, so call this method periodically if such tasks can fail.
+    pub fn take_task_errors(&self) -> Result<Vec<Error>> {
+        let Some(lua) = self.0.try_upgrade() else {
+            return Err(Error::RuntimeError("Lua VM not open".into()));
+        };
+
+        let lua = lua.lock();
+        Ok(lua.take_lute_task_errors())
+    }
+
     /// Returns a handle to the lute runtime, if it is loaded.
     ///
     /// The handle will contain references to the loaded standard libraries.
@@ -382,6 +632,110 @@ impl Lute {
     }
 }
 
+// Libraries that access the file system
+const SCOPED_LIBRARIES: &[&str] = &["fs", "vm"];
+
+// Returns the number of leading path arguments of a scoped library function.
+//
+// Functions that are not listed are not exposed when the library is scoped.
+fn scoped_path_args(library: &str, function: &str) -> Option<usize> {
+    match (library, function) {
+        ("fs", "read" | "write" | "close") => Some(0),
+        (
+            "fs",
+            "open" | "remove" | "mkdir" | "rmdir" | "exists" | "type" | "stat" | "listdir" | "watch"
+            | "readasync" | "readfiletostring" | "writestringtofile",
+        ) => Some(1),
+        ("fs", "copy" | "link" | "symlink") => Some(2),
+        ("vm", "create") => Some(1),
+        _ => None,
+    }
+}
+
+// Wraps library functions to check (and resolve) paths passed to them
+fn scope_library(lua: &Lua, library: &str, lib: &Table, root: &Path) -> Result<Table> {
+    // Lute functions may yield, so the original function must be called from Luau
+    let wrap = lua
+        .load("local f, check = ...\nreturn function(...) return f(check(...)) end")
+        .set_name("=lute_policy")
+        .into_function()?;
+
+    let scoped = lua.create_table()?;
+    for pair in lib.pairs::<Value, Value>() {
+        let (key, value) = pair?;
+        let value = match (&key, value) {
+            (Value::String(name), Value::Function(func)) => {
+                match scoped_path_args(library, &name.to_str()?) {
+                    Some(0) => Value::Function(func),
+                    Some(npaths) => {
+                        let root = root.to_path_buf();
+                        let check = lua.create_function(move |lua, args: MultiValue| {
+                            scope_args(lua, &root, npaths, args)
+                        })?;
+                        Value::Function(wrap.call::<Function>((func, check))?)
+                    }
+                    None => continue,
+                }
+            }
+            (_, value) => value,
+        };
+        scoped.raw_set(key, value)?;
+    }
+    Ok(scoped)
+}
+
+// Resolves the leading `npaths` arguments as paths inside `root`
+fn scope_args(lua: &Lua, root: &Path, npaths: usize, mut args: MultiValue) -> Result<MultiValue> {
+    for arg in args.iter_mut().take(npaths) {
+        let path = match arg {
+            Value::String(path) => resolve_scoped_path(root, &path.to_str()?)?,
+            _ => return Err(Error::RuntimeError("expected a path string".into())),
+        };
+        *arg = Value::String(lua.create_string(path.as_os_str().as_encoded_bytes())?);
+    }
+    Ok(args)
+}
+
+// Resolves `path` relative to the (canonical) `root`, returning an error if it points outside
+fn resolve_scoped_path(root: &Path, path: &str) -> Result<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in root.join(path).components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+
+    // Resolve symlinks in the existing part of the path
+    let mut existing = normalized.as_path();
+    let mut rest = Vec::new();
+    let resolved = loop {
+        match existing.canonicalize() {
+            Ok(resolved) => break rest.iter().rev().fold(resolved, |path, name| path.join(name)),
+            // A dangling symlink (checked with `symlink_metadata`) would be followed outside of
+            // the root once its target is created
+            Err(_) if existing.is_symlink() => {
+                return Err(Error::RuntimeError(format!("access to '{path}' is not allowed")));
+            }
+            Err(_) => match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    rest.push(name);
+                    existing = parent;
+                }
+                _ => break normalized.clone(),
+            },
+        }
+    };
+
+    if !resolved.starts_with(root) {
+        return Err(Error::RuntimeError(format!("access to '{path}' is not allowed")));
+    }
+    Ok(resolved)
+}
+
 impl Lua {
     /// Returns a handle to the lute runtime
     ///
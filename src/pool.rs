@@ -0,0 +1,431 @@
+//! A pool of isolated Lua states.
+
+use std::fmt;
+use std::ops::Deref;
+
+use parking_lot::Mutex;
+
+use crate::error::{Error, Result};
+use crate::state::Lua;
+use crate::table::Table;
+use crate::types::{MaybeSend, XRc};
+use crate::value::{Nil, Value};
+
+#[cfg(feature = "send")]
+type Factory = Box<dyn FnMut() -> Result<Lua> + Send>;
+
+#[cfg(not(feature = "send"))]
+type Factory = Box<dyn FnMut() -> Result<Lua>>;
+
+/// Strategy used by [`LuaPool`] to reset a Lua state when it is returned to the pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ResetStrategy {
+    /// Do not reset the state, scripts may observe changes made by previous users.
+    None,
+    /// Restore the global environment from a snapshot taken after the state was created.
+    ///
+    /// New globals are removed and changed globals are restored, as well as the metatable of the
+    /// globals table and the entries of `package.loaded` (if present).
+    ///
+    /// This is **not** an isolation boundary. The snapshot is shallow: changes made *inside*
+    /// tables stored in globals (for example `string.foo = 1`, which is also visible through the
+    /// string metatable), the registry and any other state reachable from Lua are not reverted.
+    /// Use [`ResetStrategy::Recreate`] (or [`ResetStrategy::Sandbox`] with Luau) if scripts must not
+    /// observe each other.
+    #[default]
+    RestoreGlobals,
+    /// Run scripts in a Luau sandbox and restore the original global environment when the state is
+    /// returned.
+    ///
+    /// Library tables are read-only inside the sandbox, so unlike [`ResetStrategy::RestoreGlobals`]
+    /// they cannot be polluted. See [`Lua::sandbox`] for details.
+    #[cfg(any(feature = "luau", doc))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
+    Sandbox,
+    /// Drop the state and create a new one using the pool factory.
+    Recreate,
+}
+
+/// Controls [`LuaPool`] behavior.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct LuaPoolOptions {
+    /// Maximum number of Lua states managed by the pool (both idle and checked out).
+    ///
+    /// Default: **8**
+    pub max_size: usize,
+
+    /// Strategy to reset a Lua state when it is returned to the pool.
+    ///
+    /// Default: [`ResetStrategy::RestoreGlobals`]
+    pub reset: ResetStrategy,
+
+    /// Maximum amount of memory (in bytes) a returned Lua state can use (after a full garbage
+    /// collection cycle) to be kept in the pool.
+    ///
+    /// Default: **none**
+    pub max_memory: Option<usize>,
+}
+
+impl Default for LuaPoolOptions {
+    fn default() -> Self {
+        const { LuaPoolOptions::new() }
+    }
+}
+
+impl LuaPoolOptions {
+    /// Returns a new instance of `LuaPoolOptions` with default parameters.
+    pub const fn new() -> Self {
+        LuaPoolOptions {
+            max_size: 8,
+            reset: ResetStrategy::RestoreGlobals,
+            max_memory: None,
+        }
+    }
+
+    /// Sets [`max_size`] option.
+    ///
+    /// [`max_size`]: #structfield.max_size
+    #[must_use]
+    pub const fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Sets [`reset`] option.
+    ///
+    /// [`reset`]: #structfield.reset
+    #[must_use]
+    pub const fn reset(mut self, strategy: ResetStrategy) -> Self {
+        self.reset = strategy;
+        self
+    }
+
+    /// Sets [`max_memory`] option.
+    ///
+    /// [`max_memory`]: #structfield.max_memory
+    #[must_use]
+    pub const fn max_memory(mut self, max_memory: usize) -> Self {
+        self.max_memory = Some(max_memory);
+        self
+    }
+}
+
+/// Statistics of a [`LuaPool`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LuaPoolStats {
+    /// Number of Lua states created by the pool.
+    pub created: usize,
+    /// Number of times an idle state was handed out instead of creating a new one.
+    pub reused: usize,
+    /// Number of states dropped by the pool (failed reset or health check, or recreation).
+    pub discarded: usize,
+    /// Number of idle states currently in the pool.
+    pub idle: usize,
+    /// Number of states currently checked out.
+    pub active: usize,
+}
+
+/// A pool of isolated Lua states, for example to handle requests of different tenants.
+///
+/// States are created on demand by a factory closure, checked out with [`LuaPool::checkout`] and
+/// returned to the pool when the [`PooledLua`] guard is dropped. Returned states are reset
+/// according to the [`ResetStrategy`] and checked for health (see
+/// [`LuaPoolOptions::max_memory`]) before they can be reused. States that fail the reset or the
+/// health check are discarded.
+///
+/// With the `send` feature enabled, the pool can be shared between threads.
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{Lua, LuaPool, LuaPoolOptions, Result};
+/// # fn main() -> Result<()> {
+/// let pool = LuaPool::new(|| Ok(Lua::new()), LuaPoolOptions::new().max_size(4));
+///
+/// let lua = pool.checkout()?;
+/// lua.load("polluted = true").exec()?;
+/// drop(lua);
+///
+/// let lua = pool.checkout()?;
+/// assert_eq!(lua.globals().get::<Option<bool>>("polluted")?, None);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct LuaPool(XRc<LuaPoolInner>);
+
+struct LuaPoolInner {
+    factory: Mutex<Factory>,
+    options: LuaPoolOptions,
+    state: Mutex<PoolState>,
+}
+
+struct PoolState {
+    idle: Vec<PoolEntry>,
+    stats: LuaPoolStats,
+}
+
+struct PoolEntry {
+    // Snapshot of the global environment (for `ResetStrategy::RestoreGlobals`)
+    snapshot: Option<Snapshot>,
+    lua: Lua,
+}
+
+struct Snapshot {
+    globals: Table,
+    globals_mt: Option<Table>,
+    // `package.loaded` table and its shallow copy
+    loaded: Option<(Table, Table)>,
+}
+
+impl Snapshot {
+    fn new(lua: &Lua) -> Result<Self> {
+        let globals = lua.globals();
+        let loaded = match globals.raw_get::<Value>("package")? {
+            Value::Table(package) => match package.raw_get::<Value>("loaded")? {
+                Value::Table(loaded) => Some((loaded.clone(), copy_table(lua, &loaded)?)),
+                _ => None,
+            },
+            _ => None,
+        };
+        Ok(Snapshot {
+            globals: copy_table(lua, &globals)?,
+            globals_mt: globals.metatable(),
+            loaded,
+        })
+    }
+
+    fn restore(&self, lua: &Lua) -> Result<()> {
+        let globals = lua.globals();
+        restore_table(&globals, &self.globals)?;
+        globals.set_metatable(self.globals_mt.clone());
+        if let Some((loaded, copy)) = &self.loaded {
+            restore_table(loaded, copy)?;
+        }
+        Ok(())
+    }
+}
+
+impl LuaPool {
+    /// Creates a new pool that uses `factory` to create Lua states.
+    pub fn new<F>(factory: F, options: LuaPoolOptions) -> Self
+    where
+        F: FnMut() -> Result<Lua> + MaybeSend + 'static,
+    {
+        LuaPool(XRc::new(LuaPoolInner {
+            factory: Mutex::new(Box::new(factory)),
+            options,
+            state: Mutex::new(PoolState {
+                idle: Vec::new(),
+                stats: LuaPoolStats::default(),
+            }),
+        }))
+    }
+
+    /// Takes a Lua state from the pool, or creates a new one if there are no idle states.
+    ///
+    /// Returns an error if [`LuaPoolOptions::max_size`] states are already checked out.
+    pub fn checkout(&self) -> Result<PooledLua> {
+        {
+            let mut state = self.0.state.lock();
+            if let Some(entry) = state.idle.pop() {
+                state.stats.reused += 1;
+                state.stats.active += 1;
+                return Ok(self.guard(entry));
+            }
+            if state.stats.active >= self.0.options.max_size {
+                return Err(Error::runtime("Lua pool is exhausted"));
+            }
+            // Reserve a slot while the state is being created
+            state.stats.active += 1;
+        }
+
+        match self.create_entry() {
+            Ok(entry) => {
+                self.0.state.lock().stats.created += 1;
+                Ok(self.guard(entry))
+            }
+            Err(err) => {
+                self.0.state.lock().stats.active -= 1;
+                Err(err)
+            }
+        }
+    }
+
+    /// Returns the pool options.
+    pub fn options(&self) -> &LuaPoolOptions {
+        &self.0.options
+    }
+
+    /// Returns the pool statistics.
+    pub fn stats(&self) -> LuaPoolStats {
+        let state = self.0.state.lock();
+        LuaPoolStats {
+            idle: state.idle.len(),
+            ..state.stats
+        }
+    }
+
+    /// Drops all idle Lua states.
+    pub fn clear(&self) {
+        let idle = std::mem::take(&mut self.0.state.lock().idle);
+        drop(idle);
+    }
+
+    fn guard(&self, entry: PoolEntry) -> PooledLua {
+        PooledLua {
+            entry: Some(entry),
+            pool: self.clone(),
+        }
+    }
+
+    fn create_entry(&self) -> Result<PoolEntry> {
+        let lua = (self.0.factory.lock())()?;
+        let snapshot = match self.0.options.reset {
+            ResetStrategy::RestoreGlobals => Some(Snapshot::new(&lua)?),
+            #[cfg(any(feature = "luau", doc))]
+            ResetStrategy::Sandbox => {
+                lua.sandbox(true)?;
+                None
+            }
+            _ => None,
+        };
+        Ok(PoolEntry { snapshot, lua })
+    }
+
+    fn checkin(&self, entry: PoolEntry) {
+        // Reset the state without holding the lock, as it runs Lua code
+        let entry = self.reset(entry).ok().flatten();
+
+        let mut state = self.0.state.lock();
+        state.stats.active -= 1;
+        match entry {
+            Some(entry) if state.idle.len() + state.stats.active < self.0.options.max_size => {
+                state.idle.push(entry);
+            }
+            _ => state.stats.discarded += 1,
+        }
+    }
+
+    // Resets the returned state, or returns `None` if it should be discarded
+    fn reset(&self, entry: PoolEntry) -> Result<Option<PoolEntry>> {
+        let lua = &entry.lua;
+        match self.0.options.reset {
+            ResetStrategy::None => {}
+            ResetStrategy::RestoreGlobals => {
+                if let Some(snapshot) = &entry.snapshot {
+                    snapshot.restore(lua)?;
+                }
+            }
+            #[cfg(any(feature = "luau", doc))]
+            ResetStrategy::Sandbox => {
+                lua.sandbox(false)?;
+                lua.sandbox(true)?;
+            }
+            ResetStrategy::Recreate => return Ok(None),
+        }
+
+        if let Some(max_memory) = self.0.options.max_memory {
+            lua.gc_collect()?;
+            if lua.used_memory() > max_memory {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(entry))
+    }
+}
+
+impl fmt::Debug for LuaPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LuaPool")
+            .field("options", &self.0.options)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+/// A Lua state checked out from a [`LuaPool`].
+///
+/// The state is returned to the pool when the guard is dropped.
+pub struct PooledLua {
+    entry: Option<PoolEntry>,
+    pool: LuaPool,
+}
+
+impl PooledLua {
+    /// Drops the Lua state instead of returning it to the pool.
+    pub fn discard(mut self) {
+        if let Some(entry) = self.entry.take() {
+            drop(entry);
+            let mut state = self.pool.0.state.lock();
+            state.stats.active -= 1;
+            state.stats.discarded += 1;
+        }
+    }
+}
+
+impl Deref for PooledLua {
+    type Target = Lua;
+
+    fn deref(&self) -> &Lua {
+        &self.entry.as_ref().expect("Lua state is already taken").lua
+    }
+}
+
+impl Drop for PooledLua {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            self.pool.checkin(entry);
+        }
+    }
+}
+
+impl fmt::Debug for PooledLua {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("PooledLua").field(&**self).finish()
+    }
+}
+
+// Makes a shallow copy of the table
+fn copy_table(lua: &Lua, table: &Table) -> Result<Table> {
+    let copy = lua.create_table()?;
+    for pair in table.pairs::<Value, Value>() {
+        let (key, value) = pair?;
+        copy.raw_set(key, value)?;
+    }
+    Ok(copy)
+}
+
+// Restores the table contents from a shallow copy
+fn restore_table(table: &Table, snapshot: &Table) -> Result<()> {
+    let keys = table
+        .pairs::<Value, Value>()
+        .map(|pair| pair.map(|(key, _)| key))
+        .collect::<Result<Vec<_>>>()?;
+    for key in keys {
+        if snapshot.raw_get::<Value>(&key)?.is_nil() {
+            table.raw_set(key, Nil)?;
+        }
+    }
+    for pair in snapshot.pairs::<Value, Value>() {
+        let (key, value) = pair?;
+        table.raw_set(key, value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod assertions {
+    use super::*;
+
+    #[cfg(feature = "send")]
+    static_assertions::assert_impl_all!(LuaPool: Send, Sync);
+    #[cfg(feature = "send")]
+    static_assertions::assert_impl_all!(PooledLua: Send, Sync);
+    #[cfg(not(feature = "send"))]
+    static_assertions::assert_not_impl_any!(LuaPool: Send);
+}
@@ -5,29 +5,47 @@ pub use crate::{
     AnyUserData as LuaAnyUserData, BorrowedBytes as LuaBorrowedBytes, BorrowedStr as LuaBorrowedStr,
     Chunk as LuaChunk, ContinuationStatus as LuaContinuationStatus, Either as LuaEither, Error as LuaError,
     ErrorContext as LuaErrorContext, ExternalError as LuaExternalError, ExternalResult as LuaExternalResult,
-    FromLua, FromLuaMulti, Function as LuaFunction, FunctionInfo as LuaFunctionInfo, GCMode as LuaGCMode,
-    Integer as LuaInteger, IntoLua, IntoLuaMulti, LightUserData as LuaLightUserData, Lua, LuaNativeFn,
-    LuaNativeFnMut, LuaOptions, MetaMethod as LuaMetaMethod, MultiValue as LuaMultiValue, Nil as LuaNil,
-    Number as LuaNumber, ObjectLike as LuaObjectLike, RegistryKey as LuaRegistryKey, Result as LuaResult,
-    StdLib as LuaStdLib, String as LuaString, Table as LuaTable, TablePairs as LuaTablePairs,
-    TableSequence as LuaTableSequence, Thread as LuaThread, ThreadStatus as LuaThreadStatus,
-    UserData as LuaUserData, UserDataFields as LuaUserDataFields, UserDataMetatable as LuaUserDataMetatable,
-    UserDataMethods as LuaUserDataMethods, UserDataRef as LuaUserDataRef,
-    UserDataRefMut as LuaUserDataRefMut, UserDataRegistry as LuaUserDataRegistry, Value as LuaValue,
-    Variadic as LuaVariadic, VmState as LuaVmState, WeakLua,
+    FieldType as LuaFieldType, FromLua, FromLuaMulti, Function as LuaFunction,
+    FunctionInfo as LuaFunctionInfo, GCMode as LuaGCMode, Generator as LuaGenerator, Integer as LuaInteger,
+    InterruptHandle as LuaInterruptHandle, IntoLua, IntoLuaMulti, LightUserData as LuaLightUserData, Lua,
+    LuaBytes, LuaNativeFn, LuaNativeFnMut, LuaOptions, LuaPool, LuaPoolOptions, LuaPoolStats,
+    MetaMethod as LuaMetaMethod, MultiValue as LuaMultiValue, Nil as LuaNil, Number as LuaNumber,
+    ObjectLike as LuaObjectLike, PooledLua as LuaPooledLua, PooledThread as LuaPooledThread,
+    RegistryKey as LuaRegistryKey, ResetStrategy as LuaResetStrategy, Result as LuaResult,
+    StdLib as LuaStdLib, String as LuaString, StringBuilder as LuaStringBuilder, Table as LuaTable,
+    TableCursor as LuaTableCursor, TablePairs as LuaTablePairs, TableSchema as LuaTableSchema,
+    TableSequence as LuaTableSequence, Thread as LuaThread, ThreadPool as LuaThreadPool,
+    ThreadPoolStats as LuaThreadPoolStats, ThreadStatus as LuaThreadStatus, Typed as LuaTyped,
+    TypedTable as LuaTypedTable, UserData as LuaUserData, UserDataFields as LuaUserDataFields,
+    UserDataMetatable as LuaUserDataMetatable, UserDataMethods as LuaUserDataMethods,
+    UserDataRef as LuaUserDataRef, UserDataRefMut as LuaUserDataRefMut,
+    UserDataRegistry as LuaUserDataRegistry, Value as LuaValue, Variadic as LuaVariadic,
+    VmState as LuaVmState, WeakLua,
 };
 
 #[cfg(not(feature = "luau"))]
 #[doc(no_inline)]
 pub use crate::HookTriggers as LuaHookTriggers;
 
+#[cfg(feature = "scheduler")]
+#[doc(no_inline)]
+pub use crate::Scheduler as LuaScheduler;
+
+#[cfg(feature = "actor")]
+#[doc(no_inline)]
+pub use crate::{ActorValue as LuaActorValue, Actors as LuaActors};
+
 #[cfg(feature = "luau")]
 #[doc(no_inline)]
 pub use crate::{
-    CompileConstant as LuaCompileConstant, CoverageInfo as LuaCoverageInfo,
-    NavigateError as LuaNavigateError, Require as LuaRequire, Vector as LuaVector,
+    Buffer as LuaBuffer, BufferCursor as LuaBufferCursor, CompileConstant as LuaCompileConstant,
+    CoverageInfo as LuaCoverageInfo, NavigateError as LuaNavigateError, Require as LuaRequire,
 };
 
+#[cfg(any(feature = "luau", feature = "userdata-vector"))]
+#[doc(no_inline)]
+pub use crate::Vector as LuaVector;
+
 #[cfg(feature = "serde")]
 #[doc(no_inline)]
 pub use crate::{
@@ -37,5 +55,5 @@ pub use crate::{
 #[cfg(feature = "luau-lute")]
 pub use crate::luau::lute::{
     LuteChildVmType as LuaLuteChildVmType, LuteRuntimeHandle as LuaLuteRuntimeHandle,
-    LuteStdLib as LuaLuteStdLib,
+    LuteSchedulerPoll as LuaLuteSchedulerPoll, LuteStdLib as LuaLuteStdLib, LuteVmPolicy as LuaLuteVmPolicy,
 };
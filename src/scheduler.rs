@@ -0,0 +1,406 @@
+//! A cooperative task scheduler for Lua coroutines.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
+
+use crate::error::{Error, Result};
+use crate::function::Function;
+use crate::multi::MultiValue;
+use crate::state::{Lua, WeakLua};
+use crate::table::Table;
+use crate::thread::{Thread, ThreadStatus};
+use crate::traits::IntoLuaMulti;
+use crate::types::{XRc, XWeak};
+use crate::value::Value;
+
+/// A cooperative scheduler that drives Lua coroutines.
+///
+/// The scheduler provides a `task` library to scripts with the following functions:
+///
+/// - `task.spawn(f, ...)`: runs `f` (a function or thread) immediately in a new task until it
+///   yields, and returns its thread.
+/// - `task.defer(f, ...)`: schedules `f` to run on the next [`Scheduler::step`], and returns its
+///   thread.
+/// - `task.delay(seconds, f, ...)`: schedules `f` to run after `seconds`, and returns its thread.
+/// - `task.wait([seconds])`: suspends the current task for `seconds` (or until the next step if
+///   omitted), and returns the actual elapsed time in seconds. Raises an error if called outside
+///   of a task.
+/// - `task.cancel(thread)`: cancels a scheduled task.
+///
+/// The scheduler is driven from Rust by calling [`Scheduler::step`] (for example once per frame
+/// of a game loop) or [`Scheduler::run_until_idle`]. Delays set before the first step are relative
+/// to the time of that step.
+///
+/// Tasks that yield using `coroutine.yield` are resumed (with no arguments) on the next step.
+///
+/// Errors raised by tasks do not stop the scheduler; they are collected and can be retrieved
+/// with [`Scheduler::take_errors`].
+///
+/// The `task` library does not keep the scheduler alive. Once all [`Scheduler`] handles are
+/// dropped, scheduled tasks are released and the library functions raise an error.
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{Lua, Result, Scheduler};
+/// # fn main() -> Result<()> {
+/// let lua = Lua::new();
+/// let scheduler = Scheduler::new(&lua)?;
+/// scheduler.install()?;
+///
+/// lua.load(r#"
+///     task.spawn(function()
+///         for i = 1, 3 do
+///             task.wait(0.01)
+///         end
+///         done = true
+///     end)
+/// "#).exec()?;
+///
+/// scheduler.run_until_idle()?;
+/// assert!(lua.globals().get::<bool>("done")?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Scheduler(XRc<SchedulerInner>);
+
+struct SchedulerInner {
+    lua: WeakLua,
+    library: Table,
+    state: Mutex<SchedulerState>,
+}
+
+struct SchedulerState {
+    // Time of the current (or last) step
+    now: Instant,
+    // Set after the first step, timers are rebased to its time
+    started: bool,
+    // Scheduled tasks ordered by their wake up time (and insertion order)
+    queue: BTreeMap<(Instant, u64), Task>,
+    // Index of scheduled tasks by thread pointer
+    index: FxHashMap<usize, (Instant, u64)>,
+    next_seq: u64,
+    errors: Vec<(Thread, Error)>,
+}
+
+struct Task {
+    thread: Thread,
+    resume: Resume,
+}
+
+enum Resume {
+    // Resume with the given arguments
+    Args(MultiValue),
+    // Resume with the time elapsed since the given instant
+    Elapsed(Instant),
+}
+
+impl SchedulerState {
+    fn schedule(&mut self, thread: Thread, wake_at: Instant, resume: Resume) {
+        let ptr = thread.to_pointer() as usize;
+        if let Some(key) = self.index.remove(&ptr) {
+            self.queue.remove(&key);
+        }
+        let key = (wake_at, self.next_seq);
+        self.next_seq += 1;
+        self.index.insert(ptr, key);
+        self.queue.insert(key, Task { thread, resume });
+    }
+
+    fn cancel(&mut self, thread: &Thread) -> bool {
+        match self.index.remove(&(thread.to_pointer() as usize)) {
+            Some(key) => self.queue.remove(&key).is_some(),
+            None => false,
+        }
+    }
+
+    fn is_scheduled(&self, thread: &Thread) -> bool {
+        self.index.contains_key(&(thread.to_pointer() as usize))
+    }
+
+    // Shifts all timers (scheduled before the first step) by the given offset
+    fn rebase(&mut self, offset: Duration) {
+        let queue = std::mem::take(&mut self.queue);
+        for ((wake_at, seq), mut task) in queue {
+            if let Resume::Elapsed(since) = &mut task.resume {
+                *since += offset;
+            }
+            let key = (wake_at + offset, seq);
+            self.index.insert(task.thread.to_pointer() as usize, key);
+            self.queue.insert(key, task);
+        }
+    }
+}
+
+impl Scheduler {
+    /// Creates a new scheduler for the given Lua state.
+    ///
+    /// The `task` library is not exposed to scripts until [`Scheduler::install`] is called or
+    /// the table returned by [`Scheduler::library`] is made available in some other way.
+    pub fn new(lua: &Lua) -> Result<Self> {
+        let library = lua.create_table()?;
+        let scheduler = Scheduler(XRc::new(SchedulerInner {
+            lua: lua.weak(),
+            library: library.clone(),
+            state: Mutex::new(SchedulerState {
+                now: Instant::now(),
+                started: false,
+                queue: BTreeMap::new(),
+                index: FxHashMap::default(),
+                next_seq: 0,
+                errors: Vec::new(),
+            }),
+        }));
+        scheduler.init_library(lua, &library)?;
+        Ok(scheduler)
+    }
+
+    /// Returns the `task` library table.
+    pub fn library(&self) -> Table {
+        self.0.library.clone()
+    }
+
+    /// Sets the `task` library as a global variable.
+    pub fn install(&self) -> Result<()> {
+        let lua = self.0.lua.upgrade();
+        lua.globals().set("task", self.library())
+    }
+
+    /// Schedules a function to run on the next [`Scheduler::step`], and returns its thread.
+    pub fn spawn(&self, func: Function, args: impl IntoLuaMulti) -> Result<Thread> {
+        let lua = self.0.lua.upgrade();
+        let thread = lua.create_thread(func)?;
+        let args = args.into_lua_multi(&lua)?;
+        let mut state = self.0.state.lock();
+        let now = state.now;
+        state.schedule(thread.clone(), now, Resume::Args(args));
+        Ok(thread)
+    }
+
+    /// Cancels a scheduled task.
+    ///
+    /// Returns `true` if the task was scheduled.
+    pub fn cancel(&self, thread: &Thread) -> bool {
+        self.0.state.lock().cancel(thread)
+    }
+
+    /// Resumes all tasks that are due at `now`.
+    ///
+    /// Tasks scheduled while stepping (for example by `task.defer` or `task.wait()` with no
+    /// arguments) run on the next step.
+    ///
+    /// Returns the number of resumed tasks.
+    pub fn step(&self, now: Instant) -> Result<usize> {
+        let due = {
+            let mut state = self.0.state.lock();
+            if !state.started {
+                state.started = true;
+                if let Some(offset) = now.checked_duration_since(state.now) {
+                    state.rebase(offset);
+                }
+            }
+            state.now = now;
+            let mut due = VecDeque::new();
+            while let Some(entry) = state.queue.first_entry() {
+                if entry.key().0 > now {
+                    break;
+                }
+                let task = entry.remove();
+                state.index.remove(&(task.thread.to_pointer() as usize));
+                due.push_back(task);
+            }
+            due
+        };
+
+        let lua = self.0.lua.upgrade();
+        let count = due.len();
+        for task in due {
+            let args = match task.resume {
+                Resume::Args(args) => args,
+                Resume::Elapsed(since) => now.duration_since(since).as_secs_f64().into_lua_multi(&lua)?,
+            };
+            self.resume(&task.thread, args);
+        }
+        Ok(count)
+    }
+
+    /// Runs tasks until there are no more scheduled tasks, sleeping the current thread while
+    /// waiting for timers.
+    pub fn run_until_idle(&self) -> Result<()> {
+        loop {
+            self.step(Instant::now())?;
+            match self.next_wakeup() {
+                Some(wake_at) => {
+                    let now = Instant::now();
+                    if wake_at > now {
+                        std::thread::sleep(wake_at - now);
+                    }
+                }
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Returns the time at which the next scheduled task is due, or `None` if there are no
+    /// scheduled tasks.
+    pub fn next_wakeup(&self) -> Option<Instant> {
+        let state = self.0.state.lock();
+        state.queue.first_key_value().map(|(key, _)| key.0)
+    }
+
+    /// Returns `true` if there are no scheduled tasks.
+    pub fn is_idle(&self) -> bool {
+        self.0.state.lock().queue.is_empty()
+    }
+
+    /// Returns the number of scheduled tasks.
+    pub fn task_count(&self) -> usize {
+        self.0.state.lock().queue.len()
+    }
+
+    /// Takes errors raised by tasks since the last call.
+    pub fn take_errors(&self) -> Vec<(Thread, Error)> {
+        std::mem::take(&mut self.0.state.lock().errors)
+    }
+
+    // Resumes the thread and records an error if it fails
+    fn resume(&self, thread: &Thread, args: MultiValue) {
+        if thread.status() != ThreadStatus::Resumable {
+            return;
+        }
+        match thread.resume::<()>(args) {
+            Ok(()) if thread.status() == ThreadStatus::Resumable => {
+                // The task yielded without using the scheduler (e.g. `coroutine.yield`)
+                let mut state = self.0.state.lock();
+                if !state.is_scheduled(thread) {
+                    let now = state.now;
+                    state.schedule(thread.clone(), now, Resume::Args(MultiValue::new()));
+                }
+            }
+            Ok(()) => {}
+            Err(err) => {
+                let mut state = self.0.state.lock();
+                state.cancel(thread);
+                state.errors.push((thread.clone(), err));
+            }
+        }
+    }
+
+    // Functions of the library hold a weak reference to the scheduler, as the library table is
+    // owned by the scheduler itself
+    fn init_library(&self, lua: &Lua, library: &Table) -> Result<()> {
+        let weak = XRc::downgrade(&self.0);
+        let spawn = lua.create_function(move |lua, (task, args): (Value, MultiValue)| {
+            let this = Scheduler::from_weak(&weak)?;
+            let thread = this.task_thread(lua, task)?;
+            this.0.state.lock().cancel(&thread);
+            this.resume(&thread, args);
+            Ok(thread)
+        })?;
+        library.raw_set("spawn", spawn)?;
+
+        let weak = XRc::downgrade(&self.0);
+        let defer = lua.create_function(move |lua, (task, args): (Value, MultiValue)| {
+            let this = Scheduler::from_weak(&weak)?;
+            let thread = this.task_thread(lua, task)?;
+            let mut state = this.0.state.lock();
+            let now = state.now;
+            state.schedule(thread.clone(), now, Resume::Args(args));
+            Ok(thread)
+        })?;
+        library.raw_set("defer", defer)?;
+
+        let weak = XRc::downgrade(&self.0);
+        let delay = lua.create_function(move |lua, (secs, task, args): (f64, Value, MultiValue)| {
+            let this = Scheduler::from_weak(&weak)?;
+            let delay = duration_from_secs(secs)?;
+            let thread = this.task_thread(lua, task)?;
+            let mut state = this.0.state.lock();
+            let wake_at = state.now + delay;
+            state.schedule(thread.clone(), wake_at, Resume::Args(args));
+            Ok(thread)
+        })?;
+        library.raw_set("delay", delay)?;
+
+        let weak = XRc::downgrade(&self.0);
+        let wait = lua.create_function(move |lua, secs: Option<f64>| {
+            let this = Scheduler::from_weak(&weak)?;
+            if !is_yieldable(lua) {
+                return Err(Error::runtime("attempt to wait outside of a task"));
+            }
+            let delay = duration_from_secs(secs.unwrap_or_default())?;
+            let thread = lua.current_thread();
+            let mut state = this.0.state.lock();
+            let now = state.now;
+            state.schedule(thread, now + delay, Resume::Elapsed(now));
+            drop(state);
+            lua.yield_with(())
+        })?;
+        library.raw_set("wait", wait)?;
+
+        let weak = XRc::downgrade(&self.0);
+        let cancel = lua.create_function(move |_, thread: Thread| {
+            Scheduler::from_weak(&weak)?.cancel(&thread);
+            Ok(())
+        })?;
+        library.raw_set("cancel", cancel)?;
+
+        Ok(())
+    }
+
+    fn from_weak(weak: &XWeak<SchedulerInner>) -> Result<Self> {
+        let inner = weak
+            .upgrade()
+            .ok_or_else(|| Error::runtime("task scheduler is dropped"))?;
+        Ok(Scheduler(inner))
+    }
+
+    // Returns a thread for a function (creating a new one) or a thread value
+    fn task_thread(&self, lua: &Lua, task: Value) -> Result<Thread> {
+        match task {
+            Value::Function(func) => lua.create_thread(func),
+            Value::Thread(thread) => Ok(thread),
+            _ => Err(Error::runtime(format!(
+                "expected function or thread, got {}",
+                task.type_name()
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("tasks", &self.task_count())
+            .finish()
+    }
+}
+
+// Checks that the current thread can yield (is not the main thread)
+fn is_yieldable(lua: &Lua) -> bool {
+    #[cfg(not(any(feature = "lua51", feature = "lua52", feature = "luajit")))]
+    return lua.is_yieldable();
+    #[cfg(any(feature = "lua51", feature = "lua52", feature = "luajit"))]
+    {
+        let rawlua = lua.lock();
+        rawlua.state() != rawlua.main_state()
+    }
+}
+
+fn duration_from_secs(secs: f64) -> Result<Duration> {
+    Duration::try_from_secs_f64(secs.max(0.0)).map_err(Error::runtime)
+}
+
+#[cfg(test)]
+mod assertions {
+    use super::*;
+
+    #[cfg(not(feature = "send"))]
+    static_assertions::assert_not_impl_any!(Scheduler: Send);
+    #[cfg(feature = "send")]
+    static_assertions::assert_impl_all!(Scheduler: Send, Sync);
+}
@@ -0,0 +1,89 @@
+//! CBOR module for Lua scripts.
+//!
+//! The module provides `encode(value, [options])` and `decode(data)` functions that convert
+//! between Lua values and CBOR binary strings.
+//!
+//! `encode` accepts an optional table with the following fields:
+//! - `sort_keys`: emit map keys in sorted order (default `false`)
+//!
+//! CBOR `null` and `undefined` are decoded as [`Lua::null`] and arrays are decoded as tables
+//! with the [`Lua::array_metatable`] attached, so any decoded value can be encoded back unchanged.
+//!
+//! [`Lua::null`]: crate::LuaSerdeExt::null
+//! [`Lua::array_metatable`]: crate::LuaSerdeExt::array_metatable
+
+use crate::error::{Error, Result};
+use crate::serde::codec::{deserialize_error, serialize_error, Codec, EncodeOptions};
+use crate::serde::LuaSerdeExt;
+use crate::state::Lua;
+use crate::table::Table;
+use crate::traits::IntoLua;
+use crate::value::{SerializableValue, Value};
+
+/// Name under which the module is registered by [`register`].
+pub const MODULE_NAME: &str = if cfg!(feature = "luau") { "@cbor" } else { "cbor" };
+
+/// Creates a new `cbor` module table.
+pub fn create_module(lua: &Lua) -> Result<Table> {
+    Cbor::create_module(lua)
+}
+
+/// Registers the `cbor` module using [`Lua::register_module`].
+///
+/// Scripts can then load it with `require("cbor")` (or `require("@cbor")` in Luau).
+pub fn register(lua: &Lua) -> Result<()> {
+    Cbor::register(lua)
+}
+
+struct Cbor;
+
+impl Codec for Cbor {
+    const NAME: &'static str = "cbor";
+    const MODULE_NAME: &'static str = MODULE_NAME;
+
+    fn encode(value: &SerializableValue, _: EncodeOptions) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        ciborium::ser::into_writer(value, &mut buf).map_err(serialize_error)?;
+        Ok(buf)
+    }
+
+    fn decode(lua: &Lua, data: &[u8]) -> Result<Value> {
+        let mut input = data;
+        let value = ciborium::de::from_reader::<ciborium::Value, _>(&mut input).map_err(deserialize_error)?;
+        if !input.is_empty() {
+            return Err(Self::trailing_data_error());
+        }
+        from_cbor(lua, value)
+    }
+}
+
+// Converts a decoded CBOR value to a Lua value
+//
+// ciborium does not expose its deserializer, so values cannot be decoded directly using a seed
+fn from_cbor(lua: &Lua, value: ciborium::Value) -> Result<Value> {
+    match value {
+        ciborium::Value::Integer(i) => i128::from(i).into_lua(lua),
+        ciborium::Value::Bytes(bytes) => lua.create_string(bytes).map(Value::String),
+        ciborium::Value::Float(n) => Ok(Value::Number(n)),
+        ciborium::Value::Text(text) => lua.create_string(text).map(Value::String),
+        ciborium::Value::Bool(b) => Ok(Value::Boolean(b)),
+        ciborium::Value::Null => Ok(lua.null()),
+        ciborium::Value::Array(items) => {
+            let table = lua.create_table_with_capacity(items.len(), 0)?;
+            table.set_metatable(Some(lua.array_metatable()));
+            for (i, item) in items.into_iter().enumerate() {
+                table.raw_set(i + 1, from_cbor(lua, item)?)?;
+            }
+            Ok(Value::Table(table))
+        }
+        ciborium::Value::Map(entries) => {
+            let table = lua.create_table_with_capacity(0, entries.len())?;
+            for (key, value) in entries {
+                table.raw_set(from_cbor(lua, key)?, from_cbor(lua, value)?)?;
+            }
+            Ok(Value::Table(table))
+        }
+        ciborium::Value::Tag(tag, _) => Err(Error::DeserializeError(format!("unsupported cbor tag {tag}"))),
+        _ => Err(Error::DeserializeError("unsupported cbor value".into())),
+    }
+}
@@ -0,0 +1,223 @@
+//! Shared helpers for the `json`, `msgpack` and `cbor` script modules.
+
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::state::Lua;
+use crate::string::String as LuaString;
+use crate::table::Table;
+use crate::value::{SerializableValue, Value};
+
+#[cfg(any(feature = "json", feature = "msgpack"))]
+use {
+    crate::serde::LuaSerdeExt,
+    crate::traits::IntoLua,
+    serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    std::result::Result as StdResult,
+};
+
+// Token used by `serde_json` to pass numbers when the `arbitrary_precision` feature is enabled
+#[cfg(feature = "json")]
+const JSON_NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+/// A serialization format exposed to scripts as a module with `encode` and `decode` functions.
+pub(crate) trait Codec {
+    /// Name of the format, used in error messages.
+    const NAME: &'static str;
+
+    /// Name under which the module is registered.
+    const MODULE_NAME: &'static str;
+
+    fn encode(value: &SerializableValue, options: EncodeOptions) -> Result<Vec<u8>>;
+
+    fn decode(lua: &Lua, data: &[u8]) -> Result<Value>;
+
+    fn create_module(lua: &Lua) -> Result<Table> {
+        let module = lua.create_table()?;
+        let encode = lua.create_function(|lua, (value, options): (Value, Option<Table>)| {
+            let options = EncodeOptions::from_table(options)?;
+            let value = value.to_serializable().sort_keys(options.sort_keys);
+            lua.create_string(Self::encode(&value, options)?)
+        })?;
+        module.set("encode", encode)?;
+        let decode = lua.create_function(|lua, data: LuaString| Self::decode(lua, &data.as_bytes()))?;
+        module.set("decode", decode)?;
+        Ok(module)
+    }
+
+    fn register(lua: &Lua) -> Result<()> {
+        lua.register_module(Self::MODULE_NAME, Self::create_module(lua)?)
+    }
+
+    fn trailing_data_error() -> Error {
+        Error::DeserializeError(format!("trailing data after {} value", Self::NAME))
+    }
+}
+
+/// Options accepted by the `encode` function of script modules.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct EncodeOptions {
+    #[cfg(feature = "json")]
+    pub(crate) pretty: bool,
+    pub(crate) sort_keys: bool,
+}
+
+impl EncodeOptions {
+    pub(crate) fn from_table(options: Option<Table>) -> Result<Self> {
+        let mut this = EncodeOptions::default();
+        if let Some(options) = options {
+            #[cfg(feature = "json")]
+            {
+                this.pretty = options.get::<Option<bool>>("pretty")?.unwrap_or_default();
+            }
+            this.sort_keys = options.get::<Option<bool>>("sort_keys")?.unwrap_or_default();
+        }
+        Ok(this)
+    }
+}
+
+/// Deserializes a Lua [`Value`] directly from any self-describing format.
+///
+/// Sequences become tables with the array metatable attached, and unit/none values
+/// become [`Lua::null`], so that decoded values can be encoded back without changes.
+#[cfg(any(feature = "json", feature = "msgpack"))]
+#[derive(Clone, Copy)]
+pub(crate) struct ValueSeed<'a> {
+    lua: &'a Lua,
+}
+
+#[cfg(any(feature = "json", feature = "msgpack"))]
+impl<'a> ValueSeed<'a> {
+    pub(crate) fn new(lua: &'a Lua) -> Self {
+        ValueSeed { lua }
+    }
+}
+
+#[cfg(any(feature = "json", feature = "msgpack"))]
+impl<'de> DeserializeSeed<'de> for ValueSeed<'_> {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> StdResult<Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+#[cfg(any(feature = "json", feature = "msgpack"))]
+impl<'de> Visitor<'de> for ValueSeed<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any valid value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> StdResult<Value, E> {
+        Ok(Value::Boolean(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> StdResult<Value, E> {
+        v.into_lua(self.lua).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> StdResult<Value, E> {
+        v.into_lua(self.lua).map_err(E::custom)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> StdResult<Value, E> {
+        Ok(Value::Number(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> StdResult<Value, E> {
+        self.visit_bytes(v.as_bytes())
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> StdResult<Value, E> {
+        self.lua.create_string(v).map(Value::String).map_err(E::custom)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> StdResult<Value, E> {
+        Ok(self.lua.null())
+    }
+
+    fn visit_none<E: de::Error>(self) -> StdResult<Value, E> {
+        Ok(self.lua.null())
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> StdResult<Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> StdResult<Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> StdResult<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let len = seq.size_hint().unwrap_or(0);
+        let table = self
+            .lua
+            .create_table_with_capacity(len, 0)
+            .map_err(de::Error::custom)?;
+        table.set_metatable(Some(self.lua.array_metatable()));
+        let mut i = 1;
+        while let Some(value) = seq.next_element_seed(self)? {
+            table.raw_set(i, value).map_err(de::Error::custom)?;
+            i += 1;
+        }
+        Ok(Value::Table(table))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> StdResult<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let len = map.size_hint().unwrap_or(0);
+        let table = self
+            .lua
+            .create_table_with_capacity(0, len)
+            .map_err(de::Error::custom)?;
+        let mut next_key = map.next_key_seed(self)?;
+        #[cfg(feature = "json")]
+        if matches!(&next_key, Some(Value::String(s)) if *s == JSON_NUMBER_TOKEN) {
+            let number = map.next_value::<std::string::String>()?;
+            return self.parse_json_number(&number);
+        }
+        while let Some(key) = next_key {
+            let value = map.next_value_seed(self)?;
+            table.raw_set(key, value).map_err(de::Error::custom)?;
+            next_key = map.next_key_seed(self)?;
+        }
+        Ok(Value::Table(table))
+    }
+}
+
+#[cfg(feature = "json")]
+impl ValueSeed<'_> {
+    fn parse_json_number<E: de::Error>(self, s: &str) -> StdResult<Value, E> {
+        if let Ok(i) = s.parse::<i64>() {
+            return self.visit_i64(i);
+        }
+        match s.parse::<f64>() {
+            Ok(n) => self.visit_f64(n),
+            Err(_) => Err(E::custom(format!("invalid number `{s}`"))),
+        }
+    }
+}
+
+pub(crate) fn serialize_error(err: impl fmt::Display) -> Error {
+    Error::SerializeError(err.to_string())
+}
+
+pub(crate) fn deserialize_error(err: impl fmt::Display) -> Error {
+    Error::DeserializeError(err.to_string())
+}
@@ -1,20 +1,34 @@
 //! Deserialize Lua values to a Rust data structure.
 
 use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
 use std::os::raw::c_void;
+use std::ptr;
 use std::rc::Rc;
 use std::result::Result as StdResult;
 use std::string::String as StdString;
 
 use rustc_hash::FxHashSet;
-use serde::de::{self, IntoDeserializer};
+use serde::de::{self, Deserialize, Deserializer as _, IntoDeserializer};
 
 use crate::error::{Error, Result};
+use crate::function::Function;
 use crate::table::{Table, TablePairs, TableSequence};
 use crate::userdata::AnyUserData;
+use crate::util::{join_path, PathSegment};
 use crate::value::Value;
 
 /// A struct for deserializing Lua values into Rust values.
+///
+/// Userdata values are deserialized using their [`Serialize`] implementation (see
+/// [`Lua::create_ser_userdata`]) or, if the userdata metatable has a `__tovalue` function, from
+/// the Lua value returned by that function. To clone the wrapped Rust value instead, use
+/// the [`userdata`] function.
+///
+/// [`Serialize`]: serde::Serialize
+/// [`Lua::create_ser_userdata`]: crate::Lua::create_ser_userdata
 #[derive(Debug)]
 pub struct Deserializer {
     value: Value,
@@ -54,6 +68,44 @@ pub struct Options {
     ///
     /// Default: **false**
     pub encode_empty_tables_as_array: bool,
+
+    /// If true, an attempt to deserialize a struct from a table with keys that do not match any
+    /// struct field will cause an error.
+    ///
+    /// This works like `#[serde(deny_unknown_fields)]`, but can be enabled without changing the
+    /// target type.
+    ///
+    /// Default: **false**
+    pub deny_unknown_fields: bool,
+
+    /// If true, strings will be parsed as numbers when a number is expected.
+    ///
+    /// Parsed floats are subject to the same rules as Lua numbers, so `"1.5"` cannot be
+    /// deserialized into an integer.
+    ///
+    /// Default: **false**
+    pub coerce_strings_to_numbers: bool,
+
+    /// If true, floats without a fractional part will be accepted when an integer is expected.
+    ///
+    /// Floats with a fractional part are always rejected rather than truncated.
+    ///
+    /// Default: **false**
+    pub coerce_floats_to_integers: bool,
+
+    /// If true, numbers will be converted to strings when a string is expected.
+    ///
+    /// Default: **false**
+    pub coerce_numbers_to_strings: bool,
+
+    /// If true, table fields set to [`null`] will be treated as missing instead of unit value.
+    ///
+    /// This allows `#[serde(default)]` to be applied to such fields.
+    ///
+    /// Default: **false**
+    ///
+    /// [`null`]: crate::LuaSerdeExt::null
+    pub null_fields_as_missing: bool,
 }
 
 impl Default for Options {
@@ -70,6 +122,11 @@ impl Options {
             deny_recursive_tables: true,
             sort_keys: false,
             encode_empty_tables_as_array: false,
+            deny_unknown_fields: false,
+            coerce_strings_to_numbers: false,
+            coerce_floats_to_integers: false,
+            coerce_numbers_to_strings: false,
+            null_fields_as_missing: false,
         }
     }
 
@@ -108,6 +165,51 @@ impl Options {
         self.encode_empty_tables_as_array = enabled;
         self
     }
+
+    /// Sets [`deny_unknown_fields`] option.
+    ///
+    /// [`deny_unknown_fields`]: #structfield.deny_unknown_fields
+    #[must_use]
+    pub const fn deny_unknown_fields(mut self, enabled: bool) -> Self {
+        self.deny_unknown_fields = enabled;
+        self
+    }
+
+    /// Sets [`coerce_strings_to_numbers`] option.
+    ///
+    /// [`coerce_strings_to_numbers`]: #structfield.coerce_strings_to_numbers
+    #[must_use]
+    pub const fn coerce_strings_to_numbers(mut self, enabled: bool) -> Self {
+        self.coerce_strings_to_numbers = enabled;
+        self
+    }
+
+    /// Sets [`coerce_floats_to_integers`] option.
+    ///
+    /// [`coerce_floats_to_integers`]: #structfield.coerce_floats_to_integers
+    #[must_use]
+    pub const fn coerce_floats_to_integers(mut self, enabled: bool) -> Self {
+        self.coerce_floats_to_integers = enabled;
+        self
+    }
+
+    /// Sets [`coerce_numbers_to_strings`] option.
+    ///
+    /// [`coerce_numbers_to_strings`]: #structfield.coerce_numbers_to_strings
+    #[must_use]
+    pub const fn coerce_numbers_to_strings(mut self, enabled: bool) -> Self {
+        self.coerce_numbers_to_strings = enabled;
+        self
+    }
+
+    /// Sets [`null_fields_as_missing`] option.
+    ///
+    /// [`null_fields_as_missing`]: #structfield.null_fields_as_missing
+    #[must_use]
+    pub const fn null_fields_as_missing(mut self, enabled: bool) -> Self {
+        self.null_fields_as_missing = enabled;
+        self
+    }
 }
 
 impl Deserializer {
@@ -132,6 +234,128 @@ impl Deserializer {
             visited,
         }
     }
+
+    fn deserialize_integer<'de, V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.coerced_number() {
+            #[allow(clippy::useless_conversion)]
+            Some(Value::Integer(i)) => visitor.visit_i64(i.into()),
+            Some(Value::Number(n)) if self.options.coerce_floats_to_integers => visit_number(n, visitor),
+            #[allow(clippy::useless_conversion)]
+            Some(Value::Number(n)) => visitor.visit_f64(n.into()),
+            _ => match self.value {
+                Value::Number(n) if self.options.coerce_floats_to_integers => visit_number(n, visitor),
+                _ => self.deserialize_any(visitor),
+            },
+        }
+    }
+
+    fn deserialize_float<'de, V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.coerced_number() {
+            #[allow(clippy::useless_conversion)]
+            Some(Value::Integer(i)) => visitor.visit_i64(i.into()),
+            #[allow(clippy::useless_conversion)]
+            Some(Value::Number(n)) => visitor.visit_f64(n.into()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    // Converts userdata to a Lua value using its `__tovalue` function (if any)
+    //
+    // The returned guard marks the userdata as being converted, to detect `__tovalue` functions
+    // that return the same userdata (directly or nested).
+    fn userdata_to_value(&self) -> Result<Option<(Value, RecursionGuard)>> {
+        match &self.value {
+            Value::UserData(ud) if !ud.is_serializable() => match userdata_to_value_hook(ud) {
+                Some(to_value) => {
+                    let ptr = ud.to_pointer();
+                    if self.visited.borrow().contains(&ptr) {
+                        return Err(de::Error::custom("recursive `__tovalue` conversion detected"));
+                    }
+                    let guard = RecursionGuard::from_pointer(ptr, &self.visited);
+                    Ok(Some((to_value.call(ud)?, guard)))
+                }
+                None => Ok(None),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    // Returns the string value parsed as a number, if string to number coercion is enabled
+    fn coerced_number(&self) -> Option<Value> {
+        match &self.value {
+            Value::String(s) if self.options.coerce_strings_to_numbers => {
+                s.to_str().ok().and_then(|s| parse_number(&s))
+            }
+            _ => None,
+        }
+    }
+
+    fn deserialize_table<'de, V>(
+        self,
+        fields: Option<&'static [&'static str]>,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let Some((value, _guard)) = self.userdata_to_value()? {
+            return Deserializer::from_parts(value, self.options, self.visited)
+                .deserialize_table(fields, visitor);
+        }
+
+        match self.value {
+            Value::Table(t) => {
+                let _guard = RecursionGuard::new(&t, &self.visited);
+
+                let mut deserializer = MapDeserializer {
+                    pairs: MapPairs::new(&t, self.options.sort_keys)?,
+                    key: None,
+                    value: None,
+                    fields: fields.filter(|_| self.options.deny_unknown_fields),
+                    options: self.options,
+                    visited: self.visited,
+                    processed: 0,
+                };
+                let map = visitor.visit_map(&mut deserializer)?;
+                let count = deserializer.pairs.count();
+                if count == 0 {
+                    Ok(map)
+                } else {
+                    Err(de::Error::invalid_length(
+                        deserializer.processed + count,
+                        &"fewer elements in the table",
+                    ))
+                }
+            }
+            Value::UserData(ud) if ud.is_serializable() => {
+                serde_userdata(ud, |value| value.deserialize_map(visitor))
+            }
+            value => Err(de::Error::invalid_type(
+                de::Unexpected::Other(value.type_name()),
+                &"table",
+            )),
+        }
+    }
+}
+
+macro_rules! deserialize_number {
+    ($($($method:ident)* => $impl:ident;)*) => {
+        $($(
+            #[inline]
+            fn $method<V>(self, visitor: V) -> Result<V::Value>
+            where
+                V: de::Visitor<'de>,
+            {
+                self.$impl(visitor)
+            }
+        )*)*
+    };
 }
 
 impl<'de> serde::Deserializer<'de> for Deserializer {
@@ -142,6 +366,10 @@ impl<'de> serde::Deserializer<'de> for Deserializer {
     where
         V: de::Visitor<'de>,
     {
+        if let Some((value, _guard)) = self.userdata_to_value()? {
+            return Deserializer::from_parts(value, self.options, self.visited).deserialize_any(visitor);
+        }
+
         match self.value {
             Value::Nil => visitor.visit_unit(),
             Value::Boolean(b) => visitor.visit_bool(b),
@@ -204,6 +432,11 @@ impl<'de> serde::Deserializer<'de> for Deserializer {
     where
         V: de::Visitor<'de>,
     {
+        if let Some((value, _guard)) = self.userdata_to_value()? {
+            let deserializer = Deserializer::from_parts(value, self.options, self.visited);
+            return deserializer.deserialize_enum(name, variants, visitor);
+        }
+
         let (variant, value, _guard) = match self.value {
             Value::Table(table) => {
                 let _guard = RecursionGuard::new(&table, &self.visited);
@@ -253,6 +486,10 @@ impl<'de> serde::Deserializer<'de> for Deserializer {
     where
         V: de::Visitor<'de>,
     {
+        if let Some((value, _guard)) = self.userdata_to_value()? {
+            return Deserializer::from_parts(value, self.options, self.visited).deserialize_seq(visitor);
+        }
+
         match self.value {
             #[cfg(feature = "luau")]
             Value::Vector(vec) => {
@@ -270,6 +507,7 @@ impl<'de> serde::Deserializer<'de> for Deserializer {
                 let len = t.raw_len();
                 let mut deserializer = SeqDeserializer {
                     seq: t.sequence_values(),
+                    index: 0,
                     options: self.options,
                     visited: self.visited,
                 };
@@ -283,6 +521,15 @@ impl<'de> serde::Deserializer<'de> for Deserializer {
             Value::UserData(ud) if ud.is_serializable() => {
                 serde_userdata(ud, |value| value.deserialize_seq(visitor))
             }
+            #[cfg(feature = "luau")]
+            Value::Buffer(buf) => unsafe {
+                buf.with_bytes(|data| {
+                    let mut deserializer = de::value::SeqDeserializer::<_, Error>::new(data.iter().copied());
+                    let seq = visitor.visit_seq(&mut deserializer)?;
+                    deserializer.end()?;
+                    Ok(seq)
+                })
+            },
             value => Err(de::Error::invalid_type(
                 de::Unexpected::Other(value.type_name()),
                 &"table",
@@ -311,49 +558,20 @@ impl<'de> serde::Deserializer<'de> for Deserializer {
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::Table(t) => {
-                let _guard = RecursionGuard::new(&t, &self.visited);
-
-                let mut deserializer = MapDeserializer {
-                    pairs: MapPairs::new(&t, self.options.sort_keys)?,
-                    value: None,
-                    options: self.options,
-                    visited: self.visited,
-                    processed: 0,
-                };
-                let map = visitor.visit_map(&mut deserializer)?;
-                let count = deserializer.pairs.count();
-                if count == 0 {
-                    Ok(map)
-                } else {
-                    Err(de::Error::invalid_length(
-                        deserializer.processed + count,
-                        &"fewer elements in the table",
-                    ))
-                }
-            }
-            Value::UserData(ud) if ud.is_serializable() => {
-                serde_userdata(ud, |value| value.deserialize_map(visitor))
-            }
-            value => Err(de::Error::invalid_type(
-                de::Unexpected::Other(value.type_name()),
-                &"table",
-            )),
-        }
+        self.deserialize_table(None, visitor)
     }
 
     #[inline]
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_map(visitor)
+        self.deserialize_table(Some(fields), visitor)
     }
 
     #[inline]
@@ -361,6 +579,15 @@ impl<'de> serde::Deserializer<'de> for Deserializer {
     where
         V: de::Visitor<'de>,
     {
+        if name == USERDATA_TOKEN && matches!(self.value, Value::UserData(_)) {
+            return visitor.visit_newtype_struct(UserDataDeserializer(self));
+        }
+
+        if let Some((value, _guard)) = self.userdata_to_value()? {
+            let deserializer = Deserializer::from_parts(value, self.options, self.visited);
+            return deserializer.deserialize_newtype_struct(name, visitor);
+        }
+
         match self.value {
             Value::UserData(ud) if ud.is_serializable() => {
                 serde_userdata(ud, |value| value.deserialize_newtype_struct(name, visitor))
@@ -391,14 +618,42 @@ impl<'de> serde::Deserializer<'de> for Deserializer {
         }
     }
 
+    #[inline]
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Integer(i) if self.options.coerce_numbers_to_strings => {
+                visitor.visit_string(i.to_string())
+            }
+            Value::Number(n) if self.options.coerce_numbers_to_strings => visitor.visit_string(n.to_string()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    deserialize_number! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128 => deserialize_integer;
+        deserialize_f32 deserialize_f64 => deserialize_float;
+    }
+
     serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
-        byte_buf identifier ignored_any
+        bool char bytes byte_buf identifier ignored_any
     }
 }
 
 struct SeqDeserializer<'a> {
     seq: TableSequence<'a, Value>,
+    index: usize,
     options: Options,
     visited: Rc<RefCell<FxHashSet<*const c_void>>>,
 }
@@ -414,6 +669,7 @@ impl<'de> de::SeqAccess<'de> for SeqDeserializer<'_> {
             match self.seq.next() {
                 Some(value) => {
                     let value = value?;
+                    self.index += 1;
                     let skip = check_value_for_skip(&value, self.options, &self.visited)
                         .map_err(|err| Error::DeserializeError(err.to_string()))?;
                     if skip {
@@ -421,7 +677,10 @@ impl<'de> de::SeqAccess<'de> for SeqDeserializer<'_> {
                     }
                     let visited = Rc::clone(&self.visited);
                     let deserializer = Deserializer::from_parts(value, self.options, visited);
-                    return seed.deserialize(deserializer).map(Some);
+                    return seed
+                        .deserialize(deserializer)
+                        .map(Some)
+                        .map_err(|err| with_path_segment(err, PathSegment::Index(self.index)));
                 }
                 None => return Ok(None),
             }
@@ -457,7 +716,9 @@ impl<'de> de::SeqAccess<'de> for VecDeserializer {
                 self.next += 1;
                 let visited = Rc::clone(&self.visited);
                 let deserializer = Deserializer::from_parts(Value::Number(n as _), self.options, visited);
-                seed.deserialize(deserializer).map(Some)
+                seed.deserialize(deserializer)
+                    .map(Some)
+                    .map_err(|err| with_path_segment(err, PathSegment::Index(self.next)))
             }
             None => Ok(None),
         }
@@ -512,7 +773,11 @@ impl Iterator for MapPairs<'_> {
 
 struct MapDeserializer<'a> {
     pairs: MapPairs<'a>,
+    // Current key, used to build the path to a value that failed to deserialize
+    key: Option<Value>,
     value: Option<Value>,
+    // Struct fields to check table keys against (if unknown fields are denied)
+    fields: Option<&'static [&'static str]>,
     options: Options,
     visited: Rc<RefCell<FxHashSet<*const c_void>>>,
     processed: usize,
@@ -531,7 +796,20 @@ impl MapDeserializer<'_> {
                     if skip_key || skip_value {
                         continue;
                     }
+                    if self.options.null_fields_as_missing && is_null(&value) {
+                        continue;
+                    }
+                    if let Some(fields) = self.fields {
+                        let field = match key {
+                            Value::String(ref s) => s.to_string_lossy(),
+                            ref key => PathSegment::Key(key).to_string(),
+                        };
+                        if !fields.contains(&field.as_str()) {
+                            return Err(de::Error::unknown_field(&field, fields));
+                        }
+                    }
                     self.processed += 1;
+                    self.key = Some(key.clone());
                     self.value = Some(value);
                     let visited = Rc::clone(&self.visited);
                     let key_de = Deserializer::from_parts(key, self.options, visited);
@@ -572,7 +850,10 @@ impl<'de> de::MapAccess<'de> for MapDeserializer<'_> {
         T: de::DeserializeSeed<'de>,
     {
         match self.next_value_deserializer() {
-            Ok(value_de) => seed.deserialize(value_de),
+            Ok(value_de) => seed.deserialize(value_de).map_err(|err| match self.key.take() {
+                Some(key) => with_path_segment(err, PathSegment::Key(&key)),
+                None => err,
+            }),
             Err(error) => Err(error),
         }
     }
@@ -600,17 +881,19 @@ impl<'de> de::EnumAccess<'de> for EnumDeserializer {
     where
         T: de::DeserializeSeed<'de>,
     {
-        let variant = self.variant.into_deserializer();
         let variant_access = VariantDeserializer {
+            variant: self.variant.clone(),
             value: self.value,
             options: self.options,
             visited: self.visited,
         };
+        let variant = self.variant.into_deserializer();
         seed.deserialize(variant).map(|v| (v, variant_access))
     }
 }
 
 struct VariantDeserializer {
+    variant: StdString,
     value: Option<Value>,
     options: Options,
     visited: Rc<RefCell<FxHashSet<*const c_void>>>,
@@ -634,7 +917,9 @@ impl<'de> de::VariantAccess<'de> for VariantDeserializer {
         T: de::DeserializeSeed<'de>,
     {
         match self.value {
-            Some(value) => seed.deserialize(Deserializer::from_parts(value, self.options, self.visited)),
+            Some(value) => seed
+                .deserialize(Deserializer::from_parts(value, self.options, self.visited))
+                .map_err(|err| with_path_segment(err, PathSegment::Variant(&self.variant))),
             None => Err(de::Error::invalid_type(
                 de::Unexpected::UnitVariant,
                 &"newtype variant",
@@ -650,7 +935,8 @@ impl<'de> de::VariantAccess<'de> for VariantDeserializer {
             Some(value) => serde::Deserializer::deserialize_seq(
                 Deserializer::from_parts(value, self.options, self.visited),
                 visitor,
-            ),
+            )
+            .map_err(|err| with_path_segment(err, PathSegment::Variant(&self.variant))),
             None => Err(de::Error::invalid_type(
                 de::Unexpected::UnitVariant,
                 &"tuple variant",
@@ -658,15 +944,18 @@ impl<'de> de::VariantAccess<'de> for VariantDeserializer {
         }
     }
 
-    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
         match self.value {
-            Some(value) => serde::Deserializer::deserialize_map(
+            Some(value) => serde::Deserializer::deserialize_struct(
                 Deserializer::from_parts(value, self.options, self.visited),
+                "",
+                fields,
                 visitor,
-            ),
+            )
+            .map_err(|err| with_path_segment(err, PathSegment::Variant(&self.variant))),
             None => Err(de::Error::invalid_type(
                 de::Unexpected::UnitVariant,
                 &"struct variant",
@@ -685,8 +974,12 @@ pub(crate) struct RecursionGuard {
 impl RecursionGuard {
     #[inline]
     pub(crate) fn new(table: &Table, visited: &Rc<RefCell<FxHashSet<*const c_void>>>) -> Self {
+        Self::from_pointer(table.to_pointer(), visited)
+    }
+
+    #[inline]
+    fn from_pointer(ptr: *const c_void, visited: &Rc<RefCell<FxHashSet<*const c_void>>>) -> Self {
         let visited = Rc::clone(visited);
-        let ptr = table.to_pointer();
         visited.borrow_mut().insert(ptr);
         RecursionGuard { ptr, visited }
     }
@@ -714,7 +1007,7 @@ pub(crate) fn check_value_for_skip(
                 return Ok(true); // skip
             }
         }
-        Value::UserData(ud) if ud.is_serializable() => {}
+        Value::UserData(ud) if ud.is_serializable() || userdata_to_value_hook(ud).is_some() => {}
         Value::Function(_)
         | Value::Thread(_)
         | Value::UserData(_)
@@ -729,6 +1022,165 @@ pub(crate) fn check_value_for_skip(
     Ok(false) // do not skip
 }
 
+// Returns the userdata `__tovalue` function, if defined
+fn userdata_to_value_hook(ud: &AnyUserData) -> Option<Function> {
+    let metatable = ud.metatable().ok()?;
+    metatable.get::<Option<Function>>("__tovalue").ok().flatten()
+}
+
+// Special newtype struct name used by the `userdata` function to request the userdata value
+const USERDATA_TOKEN: &str = "$__mlua_private_userdata";
+
+/// Deserializes a value of type `T` that may be passed from Lua as userdata.
+///
+/// If the Lua value is a userdata wrapping `T`, the wrapped value is cloned without any
+/// intermediate representation. Otherwise `T` is deserialized as usual.
+///
+/// This function is intended to be used with the `#[serde(deserialize_with = "...")]` attribute.
+///
+/// # Example
+///
+/// ```
+/// use mlua::{Lua, LuaSerdeExt, Result, UserData};
+/// use serde::Deserialize;
+///
+/// #[derive(Clone, Debug, PartialEq, Deserialize)]
+/// struct Color(u8, u8, u8);
+///
+/// impl UserData for Color {}
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     #[serde(deserialize_with = "mlua::serde::de::userdata")]
+///     color: Color,
+/// }
+///
+/// fn main() -> Result<()> {
+///     let lua = Lua::new();
+///     lua.globals().set("red", Color(255, 0, 0))?;
+///     let config: Config = lua.from_value(lua.load("{color = red}").eval()?)?;
+///     assert_eq!(config.color, Color(255, 0, 0));
+///     Ok(())
+/// }
+/// ```
+pub fn userdata<'de, D, T>(deserializer: D) -> StdResult<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    T: Deserialize<'de> + Clone + 'static,
+{
+    deserializer.deserialize_newtype_struct(USERDATA_TOKEN, UserDataVisitor(PhantomData))
+}
+
+struct UserDataVisitor<T>(PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for UserDataVisitor<T>
+where
+    T: Deserialize<'de> + Clone + 'static,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("userdata or any value")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> StdResult<T, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        match UserDataDeserializer::downcast(deserializer) {
+            Ok(Deserializer {
+                value: Value::UserData(ud),
+                ..
+            }) => match ud.borrow::<T>() {
+                Ok(value) => Ok(value.clone()),
+                Err(err) => Err(de::Error::custom(err)),
+            },
+            Ok(deserializer) => T::deserialize(deserializer).map_err(de::Error::custom),
+            Err(deserializer) => T::deserialize(deserializer),
+        }
+    }
+}
+
+// Passes a userdata value from `Deserializer` to `UserDataVisitor`
+struct UserDataDeserializer(Deserializer);
+
+impl UserDataDeserializer {
+    // Returns the wrapped deserializer if `D` is `UserDataDeserializer`
+    fn downcast<D>(deserializer: D) -> StdResult<Deserializer, D> {
+        if typeid::of::<D>() != typeid::of::<Self>() {
+            return Err(deserializer);
+        }
+        let deserializer = ManuallyDrop::new(deserializer);
+        // SAFETY: `D` and `UserDataDeserializer` are the same type
+        Ok(unsafe { ptr::read(&*deserializer as *const D as *const Self) }.0)
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for UserDataDeserializer {
+    type Error = Error;
+
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.0.deserialize_any(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+fn is_null(value: &Value) -> bool {
+    matches!(value, Value::LightUserData(ud) if ud.0.is_null())
+}
+
+// Visits a Lua number, passing integral values as integers
+//
+// Non-integral values are never truncated, the visitor is expected to reject them.
+fn visit_number<'de, V: de::Visitor<'de>>(n: crate::Number, visitor: V) -> Result<V::Value> {
+    #[allow(clippy::useless_conversion)]
+    let n: f64 = n.into();
+    if n.fract() == 0.0 {
+        if n >= i64::MIN as f64 && n < i64::MAX as f64 {
+            return visitor.visit_i64(n as i64);
+        }
+        if n >= 0.0 && n < u64::MAX as f64 {
+            return visitor.visit_u64(n as u64);
+        }
+    }
+    visitor.visit_f64(n)
+}
+
+// Parses a string as a Lua number
+//
+// Infinity and NaN are rejected, as Lua does not parse them either.
+fn parse_number(s: &str) -> Option<Value> {
+    let s = s.trim();
+    if let Ok(i) = s.parse() {
+        return Some(Value::Integer(i));
+    }
+    s.parse::<crate::Number>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .map(Value::Number)
+}
+
+// Prepends the path segment to a deserialization error that occurred in a nested value
+fn with_path_segment(err: Error, segment: PathSegment) -> Error {
+    let (path, message) = match err {
+        Error::DeserializeError(message) => (StdString::new(), message),
+        Error::DeserializePathError { path, message } => (path, message),
+        err => return err,
+    };
+    Error::DeserializePathError {
+        path: join_path(&segment.to_string(), &path),
+        message,
+    }
+}
+
 fn serde_userdata<V>(
     ud: AnyUserData,
     f: impl FnOnce(serde_value::Value) -> std::result::Result<V, serde_value::DeserializerError>,
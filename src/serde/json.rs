@@ -0,0 +1,63 @@
+//! JSON module for Lua scripts.
+//!
+//! The module provides `encode(value, [options])` and `decode(data)` functions that convert
+//! between Lua values and JSON text without any intermediate representation.
+//!
+//! `encode` accepts an optional table with the following fields:
+//! - `pretty`: produce pretty printed output (default `false`)
+//! - `sort_keys`: emit object keys in sorted order (default `false`)
+//!
+//! JSON `null` is decoded as [`Lua::null`] and arrays are decoded as tables with the
+//! [`Lua::array_metatable`] attached, so any decoded value can be encoded back unchanged.
+//!
+//! [`Lua::null`]: crate::LuaSerdeExt::null
+//! [`Lua::array_metatable`]: crate::LuaSerdeExt::array_metatable
+
+use serde::de::DeserializeSeed;
+
+use crate::error::Result;
+use crate::serde::codec::{deserialize_error, serialize_error, Codec, EncodeOptions, ValueSeed};
+use crate::state::Lua;
+use crate::table::Table;
+use crate::value::{SerializableValue, Value};
+
+/// Name under which the module is registered by [`register`].
+pub const MODULE_NAME: &str = if cfg!(feature = "luau") { "@json" } else { "json" };
+
+/// Creates a new `json` module table.
+pub fn create_module(lua: &Lua) -> Result<Table> {
+    Json::create_module(lua)
+}
+
+/// Registers the `json` module using [`Lua::register_module`].
+///
+/// Scripts can then load it with `require("json")` (or `require("@json")` in Luau).
+pub fn register(lua: &Lua) -> Result<()> {
+    Json::register(lua)
+}
+
+struct Json;
+
+impl Codec for Json {
+    const NAME: &'static str = "json";
+    const MODULE_NAME: &'static str = MODULE_NAME;
+
+    fn encode(value: &SerializableValue, options: EncodeOptions) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match options.pretty {
+            true => serde_json::to_writer_pretty(&mut buf, value),
+            false => serde_json::to_writer(&mut buf, value),
+        }
+        .map_err(serialize_error)?;
+        Ok(buf)
+    }
+
+    fn decode(lua: &Lua, data: &[u8]) -> Result<Value> {
+        let mut deserializer = serde_json::Deserializer::from_slice(data);
+        let value = ValueSeed::new(lua)
+            .deserialize(&mut deserializer)
+            .map_err(deserialize_error)?;
+        deserializer.end().map_err(|_| Self::trailing_data_error())?;
+        Ok(value)
+    }
+}
@@ -0,0 +1,67 @@
+//! MessagePack module for Lua scripts.
+//!
+//! The module provides `encode(value, [options])` and `decode(data)` functions that convert
+//! between Lua values and MessagePack binary strings without any intermediate representation.
+//!
+//! `encode` accepts an optional table with the following fields:
+//! - `sort_keys`: emit map keys in sorted order (default `false`)
+//!
+//! MessagePack `nil` is decoded as [`Lua::null`] and arrays are decoded as tables with the
+//! [`Lua::array_metatable`] attached, so any decoded value can be encoded back unchanged.
+//!
+//! [`Lua::null`]: crate::LuaSerdeExt::null
+//! [`Lua::array_metatable`]: crate::LuaSerdeExt::array_metatable
+
+use serde::de::DeserializeSeed;
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::serde::codec::{deserialize_error, serialize_error, Codec, EncodeOptions, ValueSeed};
+use crate::state::Lua;
+use crate::table::Table;
+use crate::value::{SerializableValue, Value};
+
+/// Name under which the module is registered by [`register`].
+pub const MODULE_NAME: &str = if cfg!(feature = "luau") {
+    "@msgpack"
+} else {
+    "msgpack"
+};
+
+/// Creates a new `msgpack` module table.
+pub fn create_module(lua: &Lua) -> Result<Table> {
+    MsgPack::create_module(lua)
+}
+
+/// Registers the `msgpack` module using [`Lua::register_module`].
+///
+/// Scripts can then load it with `require("msgpack")` (or `require("@msgpack")` in Luau).
+pub fn register(lua: &Lua) -> Result<()> {
+    MsgPack::register(lua)
+}
+
+struct MsgPack;
+
+impl Codec for MsgPack {
+    const NAME: &'static str = "msgpack";
+    const MODULE_NAME: &'static str = MODULE_NAME;
+
+    fn encode(value: &SerializableValue, _: EncodeOptions) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        let mut serializer = rmp_serde::Serializer::new(&mut buf);
+        value.serialize(&mut serializer).map_err(serialize_error)?;
+        Ok(buf)
+    }
+
+    fn decode(lua: &Lua, data: &[u8]) -> Result<Value> {
+        let mut input = data;
+        let mut deserializer = rmp_serde::Deserializer::new(&mut input);
+        let value = ValueSeed::new(lua)
+            .deserialize(&mut deserializer)
+            .map_err(deserialize_error)?;
+        if !input.is_empty() {
+            return Err(Self::trailing_data_error());
+        }
+        Ok(value)
+    }
+}
@@ -51,6 +51,16 @@ pub struct Options {
     ///
     /// Default: **false**
     pub detect_serde_json_arbitrary_precision: bool,
+
+    /// If true, serialize bytes (such as `serde_bytes::ByteBuf`) to a Luau [`Buffer`].
+    /// Otherwise they will be serialized to a Lua string.
+    ///
+    /// Default: **false**
+    ///
+    /// [`Buffer`]: crate::Buffer
+    #[cfg(feature = "luau")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
+    pub serialize_bytes_to_buffer: bool,
 }
 
 impl Default for Options {
@@ -67,6 +77,8 @@ impl Options {
             serialize_none_to_null: true,
             serialize_unit_to_null: true,
             detect_serde_json_arbitrary_precision: false,
+            #[cfg(feature = "luau")]
+            serialize_bytes_to_buffer: false,
         }
     }
 
@@ -110,6 +122,17 @@ impl Options {
         self.detect_serde_json_arbitrary_precision = enabled;
         self
     }
+
+    /// Sets [`serialize_bytes_to_buffer`] option.
+    ///
+    /// [`serialize_bytes_to_buffer`]: #structfield.serialize_bytes_to_buffer
+    #[cfg(feature = "luau")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
+    #[must_use]
+    pub const fn serialize_bytes_to_buffer(mut self, enabled: bool) -> Self {
+        self.serialize_bytes_to_buffer = enabled;
+        self
+    }
 }
 
 impl<'a> Serializer<'a> {
@@ -178,6 +201,10 @@ impl<'a> ser::Serializer for Serializer<'a> {
 
     #[inline]
     fn serialize_bytes(self, value: &[u8]) -> Result<Value> {
+        #[cfg(feature = "luau")]
+        if self.options.serialize_bytes_to_buffer {
+            return self.lua.create_buffer(value).map(Value::Buffer);
+        }
         self.lua.create_string(value).map(Value::String)
     }
 
@@ -11,21 +11,22 @@ use crate::chunk::{AsChunk, Chunk};
 use crate::error::{Error, Result};
 use crate::function::Function;
 use crate::hook::Debug;
+use crate::interrupt::InterruptHandle;
 use crate::memory::MemoryState;
 use crate::multi::MultiValue;
 use crate::state::util::get_next_spot;
 use crate::stdlib::StdLib;
-use crate::string::String;
+use crate::string::{String, StringBuilder};
 use crate::table::Table;
-use crate::thread::Thread;
+use crate::thread::{Thread, ThreadPool};
 
 #[cfg(all(not(feature = "lua51"), not(feature = "luajit")))]
 use crate::thread::ContinuationStatus;
 
 use crate::traits::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti};
 use crate::types::{
-    AppDataRef, AppDataRefMut, ArcReentrantMutexGuard, Integer, LuaType, MaybeSend, Number, ReentrantMutex,
-    ReentrantMutexGuard, RegistryKey, VmState, XRc, XWeak,
+    AppData, AppDataRef, AppDataRefMut, ArcReentrantMutexGuard, Integer, LuaType, MaybeSend, Number,
+    ReentrantMutex, ReentrantMutexGuard, RegistryKey, VmState, XRc, XWeak,
 };
 use crate::userdata::{AnyUserData, UserData, UserDataProxy, UserDataRegistry, UserDataStorage};
 use crate::util::{assert_stack, check_stack, protect_lua_closure, push_string, rawset_field, StackGuard};
@@ -42,9 +43,16 @@ use serde::Serialize;
 
 pub(crate) use extra::ExtraData;
 pub use raw::RawLua;
+pub(crate) use raw::StatePin;
 pub(crate) use util::callback_error_ext;
 
 /// Top level Lua struct which represents an instance of Lua VM.
+///
+/// The VM is closed when the last `Lua` instance is dropped, on the dropping thread. If there are
+/// live [`LuaBytes`] handles at this point, closing is deferred until the last of them is dropped
+/// (on whichever thread drops it), and the whole VM memory is kept until then.
+///
+/// [`LuaBytes`]: crate::LuaBytes
 pub struct Lua {
     pub(self) raw: XRc<ReentrantMutex<RawLua>>,
     // Controls whether garbage collection should be run on drop
@@ -602,9 +610,7 @@ impl Lua {
     #[cfg_attr(docsrs, doc(cfg(not(feature = "luau"))))]
     pub fn remove_hook(&self) {
         let lua = self.lock();
-        unsafe {
-            ffi::lua_sethook(lua.state(), None, 0, 0);
-        }
+        unsafe { lua.remove_thread_hook(lua.state()) }
     }
 
     /// Sets an interrupt function that will periodically be called by Luau VM.
@@ -659,32 +665,11 @@ impl Lua {
     where
         F: Fn(&Lua) -> Result<VmState> + MaybeSend + 'static,
     {
-        unsafe extern "C-unwind" fn interrupt_proc(state: *mut ffi::lua_State, gc: c_int) {
-            if gc >= 0 {
-                // We don't support GC interrupts since they cannot survive Lua exceptions
-                return;
-            }
-            let result = callback_error_ext(state, ptr::null_mut(), false, move |extra, _| {
-                let interrupt_cb = (*extra).interrupt_callback.clone();
-                let interrupt_cb = mlua_expect!(interrupt_cb, "no interrupt callback set in interrupt_proc");
-                if XRc::strong_count(&interrupt_cb) > 2 {
-                    return Ok(VmState::Continue); // Don't allow recursion
-                }
-                interrupt_cb((*extra).lua())
-            });
-            match result {
-                VmState::Continue => {}
-                VmState::Yield => {
-                    ffi::lua_yield(state, 0);
-                }
-            }
-        }
-
         // Set interrupt callback
         let lua = self.lock();
         unsafe {
             (*lua.extra.get()).interrupt_callback = Some(XRc::new(callback));
-            (*ffi::lua_callbacks(lua.main_state())).interrupt = Some(interrupt_proc);
+            (*ffi::lua_callbacks(lua.main_state())).interrupt = Some(Self::interrupt_proc);
         }
     }
 
@@ -696,11 +681,53 @@ impl Lua {
     pub fn remove_interrupt(&self) {
         let lua = self.lock();
         unsafe {
-            (*lua.extra.get()).interrupt_callback = None;
-            (*ffi::lua_callbacks(lua.main_state())).interrupt = None;
+            let extra = lua.extra.get();
+            (*extra).interrupt_callback = None;
+            // Keep the callback to check interrupt handles
+            if !(*extra).interrupts.has_handles() {
+                (*ffi::lua_callbacks(lua.main_state())).interrupt = None;
+            }
         }
     }
 
+    #[cfg(feature = "luau")]
+    unsafe extern "C-unwind" fn interrupt_proc(state: *mut ffi::lua_State, gc: c_int) {
+        if gc >= 0 {
+            // We don't support GC interrupts since they cannot survive Lua exceptions
+            return;
+        }
+        let extra = ExtraData::get(state);
+        if (*extra).interrupt_callback.is_none() && !(*extra).interrupts.is_pending() {
+            return;
+        }
+        let result = callback_error_ext(state, extra, false, move |extra, _| {
+            (*extra).interrupts.check(state)?;
+            let interrupt_cb = match (*extra).interrupt_callback {
+                Some(ref cb) => cb.clone(),
+                None => return Ok(VmState::Continue),
+            };
+            if XRc::strong_count(&interrupt_cb) > 2 {
+                return Ok(VmState::Continue); // Don't allow recursion
+            }
+            interrupt_cb((*extra).lua())
+        });
+        match result {
+            VmState::Continue => {}
+            VmState::Yield => {
+                ffi::lua_yield(state, 0);
+            }
+        }
+    }
+
+    /// Returns a handle that can be used to interrupt running Lua code from any thread.
+    ///
+    /// Interrupting makes the running code (on any Lua thread) fail with [`Error::Interrupted`].
+    /// See [`InterruptHandle`] for details.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        let lua = self.lock();
+        unsafe { lua.interrupt_handle(ptr::null_mut(), None) }
+    }
+
     /// Sets a thread creation callback that will be called when a thread is created.
     #[cfg(any(feature = "luau", doc))]
     #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
@@ -756,14 +783,10 @@ impl Lua {
             })
         } else {
             // Thread is about to be collected
-            let callback = match (*extra).thread_collection_callback {
-                Some(ref cb) => cb.clone(),
-                None => return,
-            };
-
-            // We need to wrap the callback call in non-unwind function as it's not safe to unwind when
-            // Luau GC is running.
-            // This will trigger `abort()` if the callback panics.
+            //
+            // We need to wrap the callback call (and the app data drop) in non-unwind functions as it's
+            // not safe to unwind when Luau GC is running.
+            // This will trigger `abort()` if the callback or destructors panic.
             unsafe extern "C" fn run_callback(
                 callback: *const crate::types::ThreadCollectionCallback,
                 value: *mut ffi::lua_State,
@@ -771,6 +794,23 @@ impl Lua {
                 (*callback)(crate::LightUserData(value as _));
             }
 
+            unsafe extern "C" fn drop_app_data(data: *mut crate::types::AppData) {
+                drop(Box::from_raw(data));
+            }
+
+            let app_data = ffi::lua_getthreaddata(child) as *mut crate::types::AppData;
+            if (*extra).thread_app_data_used && !app_data.is_null() && ffi::lua_mainthread(child) != child {
+                ffi::lua_setthreaddata(child, ptr::null_mut());
+                (*extra).running_gc = true;
+                drop_app_data(app_data);
+                (*extra).running_gc = false;
+            }
+
+            let callback = match (*extra).thread_collection_callback {
+                Some(ref cb) => cb.clone(),
+                None => return,
+            };
+
             (*extra).running_gc = true;
             run_callback(&callback, child);
             (*extra).running_gc = false;
@@ -789,7 +829,10 @@ impl Lua {
             let extra = lua.extra.get();
             (*extra).thread_creation_callback = None;
             (*extra).thread_collection_callback = None;
-            (*ffi::lua_callbacks(lua.main_state())).userthread = None;
+            // Keep the callback to drop threads application data
+            if !(*extra).thread_app_data_used {
+                (*ffi::lua_callbacks(lua.main_state())).userthread = None;
+            }
         }
     }
 
@@ -1147,6 +1190,14 @@ impl Lua {
         unsafe { self.lock().create_string(s) }
     }
 
+    /// Creates a new [`StringBuilder`] to build a Lua string incrementally.
+    ///
+    /// This is more efficient than assembling data in Rust and calling [`Lua::create_string`]
+    /// when building large strings, as data is accumulated directly inside Lua.
+    pub fn create_string_builder(&self) -> Result<StringBuilder> {
+        unsafe { StringBuilder::new(&self.lock()) }
+    }
+
     /// Create and return a Luau [buffer] object from a byte slice of data.
     ///
     /// [buffer]: https://luau.org/library#buffer-library
@@ -1168,6 +1219,27 @@ impl Lua {
         }
     }
 
+    /// Create and return a Luau [buffer] object of the given size.
+    ///
+    /// Luau buffers have a fixed size, the returned buffer is filled with zeros.
+    ///
+    /// [buffer]: https://luau.org/library#buffer-library
+    #[cfg(any(feature = "luau", doc))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
+    pub fn create_buffer_with_capacity(&self, size: usize) -> Result<Buffer> {
+        let lua = self.lock();
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 3)?;
+            // Always protect the call as Luau raises an error if the size exceeds the limit
+            protect_lua!(state, 0, 1, |state| {
+                ffi::lua_newbuffer(state, size);
+            })?;
+            Ok(Buffer(lua.pop_ref()))
+        }
+    }
+
     /// Creates and returns a new empty table.
     #[inline]
     pub fn create_table(&self) -> Result<Table> {
@@ -1333,6 +1405,22 @@ impl Lua {
         unsafe { self.lock().create_thread(&func) }
     }
 
+    /// Creates a pool of reusable threads bound to the given function.
+    ///
+    /// The pool keeps at most `max_size` idle threads. See [`ThreadPool`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the function was created from a different Lua state.
+    #[track_caller]
+    pub fn create_thread_pool(&self, func: Function, max_size: usize) -> ThreadPool {
+        assert!(
+            self.lock().weak() == &func.0.lua,
+            "Lua instance passed Function created from a different main Lua state"
+        );
+        ThreadPool::new(func, max_size)
+    }
+
     /// Creates a Lua userdata object from a custom userdata type.
     ///
     /// All userdata instances of the same type `T` shares the same metatable.
@@ -1963,6 +2051,34 @@ impl Lua {
         extra.app_data.remove()
     }
 
+    /// Gets a reference to an application data object of type `T` associated with the currently
+    /// running thread.
+    ///
+    /// See [`Thread::set_app_data`] for examples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data object of type `T` is currently mutably borrowed.
+    #[track_caller]
+    pub fn current_thread_data<T: 'static>(&self) -> Option<AppDataRef<'_, T>> {
+        let guard = self.lock_arc();
+        let app_data = unsafe { guard.thread_app_data(guard.state(), false).ok()?? as *const AppData };
+        unsafe { &*app_data }.borrow(Some(guard))
+    }
+
+    /// Gets a mutable reference to an application data object of type `T` associated with the
+    /// currently running thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data object of type `T` is currently borrowed.
+    #[track_caller]
+    pub fn current_thread_data_mut<T: 'static>(&self) -> Option<AppDataRefMut<'_, T>> {
+        let guard = self.lock_arc();
+        let app_data = unsafe { guard.thread_app_data(guard.state(), false).ok()?? as *const AppData };
+        unsafe { &*app_data }.borrow_mut(Some(guard))
+    }
+
     /// Returns a weak reference to the Lua instance.
     ///
     /// This is useful for creating a reference to the Lua instance that does not prevent it from
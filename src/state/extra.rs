@@ -10,7 +10,8 @@ use parking_lot::Mutex;
 use rustc_hash::FxHashMap;
 
 use crate::error::Result;
-use crate::state::RawLua;
+use crate::interrupt::Interrupts;
+use crate::state::{RawLua, StatePin};
 use crate::stdlib::StdLib;
 use crate::types::{AppData, ReentrantMutex, XRc};
 
@@ -24,7 +25,10 @@ use crate::MultiValue;
 use super::{Lua, WeakLua};
 
 #[cfg(feature = "luau-lute")]
-use crate::luau::lute::{LuteChildVmType, LuteRuntimeHandle};
+use crate::{
+    error::Error,
+    luau::lute::{LuteChildVmType, LuteRuntimeHandle, LuteVmPolicy},
+};
 
 // Unique key to store `ExtraData` in the registry
 static EXTRA_REGISTRY_KEY: u8 = 0;
@@ -81,13 +85,22 @@ pub(crate) struct ExtraData {
     pub(super) registered_userdata_t: FxHashMap<TypeId, c_int>,
     pub(super) registered_userdata_mt: FxHashMap<*const c_void, Option<TypeId>>,
     pub(super) last_checked_userdata_mt: (*const c_void, Option<TypeId>),
+    #[cfg(feature = "luau")]
+    pub(super) registered_userdata_tags: FxHashMap<TypeId, c_int>,
+    #[cfg(feature = "luau")]
+    pub(super) next_userdata_tag: c_int,
 
     // When Lua instance dropped, setting `None` would prevent collecting `RegistryKey`s
     pub(super) registry_unref_list: Arc<Mutex<Option<Vec<c_int>>>>,
+    // Shared with `LuaBytes` handles, the Lua state is not closed while any of them is alive
+    pub(crate) lua_bytes_pin: XRc<StatePin>,
 
     // Containers to store arbitrary data (extensions)
     pub(super) app_data: AppData,
     pub(super) app_data_priv: AppData,
+    // Application data of the main thread (Luau only, other threads use thread data)
+    #[cfg(feature = "luau")]
+    pub(super) main_thread_app_data: AppData,
 
     pub(super) safe: bool,
     pub(super) libs: StdLib,
@@ -114,10 +127,15 @@ pub(crate) struct ExtraData {
     pub(super) warn_callback: Option<crate::types::WarnCallback>,
     #[cfg(feature = "luau")]
     pub(super) interrupt_callback: Option<crate::types::InterruptCallback>,
+    // Handles to interrupt running code from other threads
+    pub(super) interrupts: Interrupts,
     #[cfg(feature = "luau")]
     pub(super) thread_creation_callback: Option<crate::types::ThreadCreationCallback>,
     #[cfg(feature = "luau")]
     pub(super) thread_collection_callback: Option<crate::types::ThreadCollectionCallback>,
+    // Set when any non-main thread has application data attached
+    #[cfg(feature = "luau")]
+    pub(super) thread_app_data_used: bool,
 
     #[cfg(feature = "luau")]
     pub(crate) running_gc: bool,
@@ -136,6 +154,11 @@ pub(crate) struct ExtraData {
         Option<Box<dyn Fn(&Lua, &Lua, LuteChildVmType) -> Result<()> + Send + Sync + 'static>>,
     #[cfg(all(feature = "luau-lute", not(feature = "send")))]
     pub(crate) lute_runtimeinitter: Option<Box<dyn Fn(&Lua, &Lua, LuteChildVmType) -> Result<()> + 'static>>,
+    #[cfg(feature = "luau-lute")]
+    pub(crate) lute_child_policy: Option<LuteVmPolicy>,
+    // Errors of unrelated tasks collected by `Lute::run_until_complete`
+    #[cfg(feature = "luau-lute")]
+    pub(crate) lute_task_errors: Vec<Error>,
 
     // Child lua VM's may not be dropped from mluau
     #[cfg(feature = "luau-lute")]
@@ -190,9 +213,16 @@ impl ExtraData {
             registered_userdata_t: FxHashMap::default(),
             registered_userdata_mt: FxHashMap::default(),
             last_checked_userdata_mt: (ptr::null(), None),
+            #[cfg(feature = "luau")]
+            registered_userdata_tags: FxHashMap::default(),
+            #[cfg(feature = "luau")]
+            next_userdata_tag: crate::util::FIRST_USERDATA_TAG,
             registry_unref_list: Arc::new(Mutex::new(Some(Vec::new()))),
+            lua_bytes_pin: XRc::new(StatePin::default()),
             app_data: AppData::default(),
             app_data_priv: AppData::default(),
+            #[cfg(feature = "luau")]
+            main_thread_app_data: AppData::default(),
             safe: false,
             libs: StdLib::NONE,
             skip_memory_check: false,
@@ -209,11 +239,14 @@ impl ExtraData {
             warn_callback: None,
             #[cfg(feature = "luau")]
             interrupt_callback: None,
+            interrupts: Interrupts::default(),
             #[cfg(feature = "luau")]
             thread_creation_callback: None,
             #[cfg(feature = "luau")]
             thread_collection_callback: None,
             #[cfg(feature = "luau")]
+            thread_app_data_used: false,
+            #[cfg(feature = "luau")]
             sandboxed: false,
             #[cfg(feature = "luau")]
             compiler: None,
@@ -226,6 +259,10 @@ impl ExtraData {
             #[cfg(feature = "luau-lute")]
             lute_runtimeinitter: None,
             #[cfg(feature = "luau-lute")]
+            lute_child_policy: None,
+            #[cfg(feature = "luau-lute")]
+            lute_task_errors: Vec::new(),
+            #[cfg(feature = "luau-lute")]
             no_drop: false,
             yielded_values: None,
         }));
@@ -7,9 +7,12 @@ use std::panic::resume_unwind;
 use std::ptr::{self, NonNull};
 use std::sync::Arc;
 
+use parking_lot::Mutex;
+
 use crate::chunk::ChunkMode;
 use crate::error::{Error, Result};
 use crate::function::Function;
+use crate::interrupt::InterruptHandle;
 use crate::memory::{MemoryState, ALLOCATOR};
 #[allow(unused_imports)]
 use crate::state::util::callback_error_ext;
@@ -20,8 +23,8 @@ use crate::table::Table;
 use crate::thread::Thread;
 use crate::traits::IntoLua;
 use crate::types::{
-    AppDataRef, AppDataRefMut, Callback, CallbackUpvalue, DestructedUserdata, Integer, LightUserData,
-    MaybeSend, ReentrantMutex, RegistryKey, ValueRef, XRc,
+    AppData, AppDataRef, AppDataRefMut, Callback, CallbackUpvalue, DestructedUserdata, Integer,
+    LightUserData, MaybeSend, ReentrantMutex, RegistryKey, ValueRef, XRc,
 };
 
 #[cfg(feature = "luau")]
@@ -32,6 +35,8 @@ use crate::types::Continuation;
 #[cfg(all(not(feature = "lua51"), not(feature = "luajit")))]
 use crate::types::ContinuationUpvalue;
 
+#[cfg(feature = "luau")]
+use crate::userdata::collect_userdata;
 use crate::userdata::{
     init_userdata_metatable, AnyUserData, MetaMethod, RawUserDataRegistry, UserData, UserDataRegistry,
     UserDataStorage,
@@ -54,7 +59,9 @@ use crate::{
 };
 
 #[cfg(feature = "luau-lute")]
-use crate::luau::lute::{LuteChildVmType, LuteRuntimeHandle, LuteSchedulerRunOnceResult, LuteStdLib};
+use crate::luau::lute::{
+    LuteChildVmType, LuteRuntimeHandle, LuteSchedulerRunOnceResult, LuteStdLib, LuteVmPolicy,
+};
 
 #[cfg(feature = "luau-lute")]
 use std::sync::LazyLock;
@@ -88,6 +95,10 @@ static SETUP_LUTE_RUNTIME_INITTER: LazyLock<()> = LazyLock::new(|| {
 
                     mlua_expect!(lua.configure_luau(), "Error configuring Luau");
 
+                    if let Some(policy) = &(*extra).lute_child_policy {
+                        lua.lute()?.apply_policy(policy)?;
+                    }
+
                     if let Some(lute_runtimeinitter) = &(*extra).lute_runtimeinitter {
                         lute_runtimeinitter(parent_lua, &lua, LuteChildVmType::ChildVm)?;
                     }
@@ -163,32 +174,81 @@ impl Drop for RawLua {
 
             println!("Dropping Lua state: {:?}", self.main_state());
 
-            #[cfg(feature = "luau-lute")]
-            {
-                // SAFETY: lutec_isruntimeloaded and lutec_destroy_runtime
-                // do not need any extra stack space and should not
-                // throw an exception
-                if ffi::lutec_isruntimeloaded(self.main_state()) == 1 {
-                    ffi::lutec_destroy_runtime(self.main_state());
-                }
-            }
+            // `LuaBytes` handles point to strings owned by the Lua state, so closing is postponed until
+            // the last of them is dropped (usually it's done right away, when no handles are alive)
+            let extra = self.extra.get();
+            let pin = mem::replace(&mut (*extra).lua_bytes_pin, XRc::new(StatePin::default()));
+            *pin.0.lock() = Some(ClosingState {
+                state: self.main_state(),
+                #[cfg(feature = "luau")]
+                thread_app_data_used: (*extra).thread_app_data_used,
+            });
+        }
+    }
+}
 
-            let mem_state = MemoryState::get(self.main_state());
+/// Keeps the Lua state open while [`LuaBytes`] handles reference strings owned by it.
+///
+/// When [`RawLua`] is dropped, it hands over the state to the pin, which closes it once the last
+/// reference to the pin is released.
+///
+/// [`LuaBytes`]: crate::LuaBytes
+#[derive(Default)]
+pub(crate) struct StatePin(Mutex<Option<ClosingState>>);
 
-            #[cfg(feature = "luau")]
-            {
-                // Reset any callbacks
-                (*ffi::lua_callbacks(self.main_state())).interrupt = None;
-                (*ffi::lua_callbacks(self.main_state())).userthread = None;
-            }
+// The state is handed over only after the last `Lua` instance is gone
+#[cfg(feature = "send")]
+unsafe impl Send for StatePin {}
+#[cfg(feature = "send")]
+unsafe impl Sync for StatePin {}
 
-            ffi::lua_close(self.main_state());
+pub(crate) struct ClosingState {
+    state: *mut ffi::lua_State,
+    #[cfg(feature = "luau")]
+    thread_app_data_used: bool,
+}
 
-            // Deallocate `MemoryState`
-            if !mem_state.is_null() {
-                drop(Box::from_raw(mem_state));
+impl Drop for StatePin {
+    fn drop(&mut self) {
+        if let Some(closing) = self.0.get_mut().take() {
+            unsafe { closing.close() };
+        }
+    }
+}
+
+impl ClosingState {
+    unsafe fn close(self) {
+        let state = self.state;
+
+        #[cfg(feature = "luau-lute")]
+        {
+            // SAFETY: lutec_isruntimeloaded and lutec_destroy_runtime
+            // do not need any extra stack space and should not
+            // throw an exception
+            if ffi::lutec_isruntimeloaded(state) == 1 {
+                ffi::lutec_destroy_runtime(state);
             }
         }
+
+        let mem_state = MemoryState::get(state);
+
+        #[cfg(feature = "luau")]
+        {
+            // Reset any callbacks, but keep dropping threads application data while closing
+            let callbacks = ffi::lua_callbacks(state);
+            (*callbacks).interrupt = None;
+            (*callbacks).userthread = match self.thread_app_data_used {
+                true => Some(close_thread_proc),
+                false => None,
+            };
+        }
+
+        ffi::lua_close(state);
+
+        // Deallocate `MemoryState`
+        if !mem_state.is_null() {
+            drop(Box::from_raw(mem_state));
+        }
     }
 }
 
@@ -372,6 +432,21 @@ impl RawLua {
         }
     }
 
+    #[cfg(feature = "luau-lute")]
+    pub(crate) fn set_lute_child_policy(&self, policy: Option<LuteVmPolicy>) {
+        unsafe { (*self.extra.get()).lute_child_policy = policy };
+    }
+
+    #[cfg(feature = "luau-lute")]
+    pub(crate) fn push_lute_task_error(&self, err: Error) {
+        unsafe { (*self.extra.get()).lute_task_errors.push(err) };
+    }
+
+    #[cfg(feature = "luau-lute")]
+    pub(crate) fn take_lute_task_errors(&self) -> Vec<Error> {
+        unsafe { mem::take(&mut (*self.extra.get()).lute_task_errors) }
+    }
+
     /// Returns if the Lute scheduler has any work to do.
     #[cfg(feature = "luau-lute")]
     pub(crate) fn has_lute_work(&self) -> Result<bool> {
@@ -413,22 +488,34 @@ impl RawLua {
     #[cfg(feature = "luau-lute")]
     /// Runs one iteration of the Lute scheduler.
     pub(crate) fn lute_run_once(&self) -> Result<LuteSchedulerRunOnceResult> {
+        self.lute_run_once_traced().map_err(|(err, _)| err)
+    }
+
+    #[cfg(feature = "luau-lute")]
+    /// Runs one iteration of the Lute scheduler.
+    ///
+    /// On error, also returns the state of the thread that raised it (or null if the error did not
+    /// come from a scheduled thread).
+    pub(crate) fn lute_run_once_traced(
+        &self,
+    ) -> std::result::Result<LuteSchedulerRunOnceResult, (Error, *mut ffi::lua_State)> {
         let state = self.main_state();
+        let untraced = |err: Error| (err, ptr::null_mut());
 
         unsafe {
             let _sg = StackGuard::new(state);
-            check_stack(state, 1)?;
+            check_stack(state, 1).map_err(untraced)?;
             let res = ffi::lutec_run_once(state);
 
             match res.op {
                 ffi::LUTE_STATE_MISSING_ERROR => {
-                    return Err(Error::external("Lute runtime is not loaded"));
+                    return Err(untraced(Error::external("Lute runtime is not loaded")));
                 }
                 ffi::LUTE_STATE_ERROR => {
                     // Pop the error
-                    check_stack(res.state, 2)?;
+                    check_stack(res.state, 2).map_err(untraced)?;
                     let err = pop_error(res.state, ffi::LUA_ERRRUN);
-                    return Err(err);
+                    return Err((err, res.state));
                 }
                 ffi::LUTE_STATE_SUCCESS => {
                     let main_state = ffi::lua_mainthread(res.state);
@@ -436,7 +523,7 @@ impl RawLua {
                     let rawlua = (*extra).raw_lua();
 
                     //res.state is now a nice thread
-                    check_stack(res.state, 1)?;
+                    check_stack(res.state, 1).map_err(untraced)?;
                     let (aux_thread, idxs, replace) = get_next_spot(extra);
                     ffi::lua_pushthread(res.state);
                     ffi::lua_xmove(res.state, rawlua.ref_thread(aux_thread), 1);
@@ -452,7 +539,7 @@ impl RawLua {
                     return Ok(LuteSchedulerRunOnceResult::Empty);
                 }
                 ffi::LUTE_STATE_UNSUPPORTED_OP => {
-                    return Err(Error::external("Unsupported Lute operation"));
+                    return Err(untraced(Error::external("Unsupported Lute operation")));
                 }
                 _ => unreachable!(),
             }
@@ -588,6 +675,8 @@ impl RawLua {
                 init_internal_metatable::<NamecallMapUpvalue>(state, None)?;
                 #[cfg(not(feature = "luau"))]
                 init_internal_metatable::<HookCallback>(state, None)?;
+                #[cfg(not(feature = "luau"))]
+                init_internal_metatable::<AppData>(state, None)?;
 
                 // Init serde metatables
                 #[cfg(feature = "serde")]
@@ -711,6 +800,93 @@ impl RawLua {
         extra.app_data_priv.borrow_mut(None)
     }
 
+    /// Returns the application data container associated with the given thread.
+    ///
+    /// If `create` is `true`, a new container is created when missing.
+    /// The container lives as long as the thread (it is dropped when the thread is collected).
+    ///
+    /// On Luau, the container is stored as the thread data (`lua_setthreaddata`), except for the
+    /// main thread which uses a container in [`ExtraData`] (the main thread data may be owned by
+    /// the embedder). Other Lua versions use a registry table with weak keys.
+    #[cfg(feature = "luau")]
+    pub(crate) unsafe fn thread_app_data(
+        &self,
+        thread: *mut ffi::lua_State,
+        create: bool,
+    ) -> Result<Option<&AppData>> {
+        let extra = self.extra.get();
+        if ffi::lua_mainthread(thread) == thread {
+            return Ok(Some(&(*extra).main_thread_app_data));
+        }
+        let data = ffi::lua_getthreaddata(thread) as *const AppData;
+        if !data.is_null() || !create {
+            return Ok(data.as_ref());
+        }
+        let data = Box::into_raw(Box::<AppData>::default());
+        ffi::lua_setthreaddata(thread, data as *mut c_void);
+        // Install the callback to drop the container when the thread is collected
+        (*extra).thread_app_data_used = true;
+        (*ffi::lua_callbacks(self.main_state())).userthread = Some(Lua::userthread_proc);
+        Ok(Some(&*data))
+    }
+
+    #[cfg(not(feature = "luau"))]
+    pub(crate) unsafe fn thread_app_data(
+        &self,
+        thread: *mut ffi::lua_State,
+        create: bool,
+    ) -> Result<Option<&AppData>> {
+        static THREAD_APP_DATA_REGISTRY_KEY: u8 = 0;
+        let key = &THREAD_APP_DATA_REGISTRY_KEY as *const u8 as *const c_void;
+
+        let state = self.state();
+        let _sg = StackGuard::new(state);
+        check_stack(state, 6)?;
+
+        if ffi::lua_rawgetp(state, ffi::LUA_REGISTRYINDEX, key) == ffi::LUA_TNIL {
+            if !create {
+                return Ok(None);
+            }
+            ffi::lua_pop(state, 1);
+            push_table(state, 0, 0, true)?;
+            push_table(state, 0, 1, true)?;
+            push_string(state, b"k", true)?;
+            rawset_field(state, -2, "__mode")?;
+            ffi::lua_setmetatable(state, -2);
+            ffi::lua_pushvalue(state, -1);
+            protect_lua!(state, 1, 0, |state| {
+                ffi::lua_rawsetp(state, ffi::LUA_REGISTRYINDEX, key)
+            })?;
+        }
+
+        self.push_thread(thread)?;
+        ffi::lua_rawget(state, -2);
+        let data = get_internal_userdata::<AppData>(state, -1, ptr::null());
+        if !data.is_null() || !create {
+            return Ok(data.as_ref());
+        }
+
+        ffi::lua_pop(state, 1);
+        self.push_thread(thread)?;
+        let data = push_internal_userdata(state, AppData::default(), true)?;
+        protect_lua!(state, 3, 0, |state| ffi::lua_rawset(state, -3))?;
+        Ok(Some(&*data))
+    }
+
+    // Pushes the given thread onto the current stack
+    #[cfg(not(feature = "luau"))]
+    unsafe fn push_thread(&self, thread: *mut ffi::lua_State) -> Result<()> {
+        let state = self.state();
+        if thread == state {
+            ffi::lua_pushthread(state);
+        } else {
+            check_stack(thread, 1)?;
+            ffi::lua_pushthread(thread);
+            ffi::lua_xmove(thread, state, 1);
+        }
+        Ok(())
+    }
+
     /// See [`Lua::create_registry_value`]
     #[inline]
     pub(crate) fn owns_registry_value(&self, key: &RegistryKey) -> bool {
@@ -814,6 +990,7 @@ impl RawLua {
 
         unsafe extern "C-unwind" fn global_hook_proc(state: *mut ffi::lua_State, ar: *mut ffi::lua_Debug) {
             let status = callback_error_ext(state, ptr::null_mut(), false, move |extra, _| {
+                (*extra).interrupts.check(state)?;
                 match (*extra).hook_callback.clone() {
                     Some(hook_callback) => {
                         let rawlua = (*extra).raw_lua();
@@ -821,7 +998,8 @@ impl RawLua {
                         hook_callback((*extra).lua(), debug)
                     }
                     None => {
-                        ffi::lua_sethook(state, None, 0, 0);
+                        // Keep the interrupt hook if there are interrupt handles
+                        (*extra).raw_lua().remove_thread_hook(state);
                         Ok(VmState::Continue)
                     }
                 }
@@ -841,11 +1019,12 @@ impl RawLua {
             }
             ffi::lua_settop(state, top);
             if hook_callback_ptr.is_null() {
-                ffi::lua_sethook(state, None, 0, 0);
+                (*ExtraData::get(state)).raw_lua().remove_thread_hook(state);
                 return;
             }
 
             let status = callback_error_ext(state, ptr::null_mut(), false, |extra, _| {
+                (*extra).interrupts.check(state)?;
                 let rawlua = (*extra).raw_lua();
                 let debug = Debug::new(rawlua, ar);
                 let hook_callback = (*hook_callback_ptr).clone();
@@ -891,6 +1070,56 @@ impl RawLua {
         Ok(())
     }
 
+    /// Removes a hook function from the thread, keeping the interrupt hook if needed.
+    #[cfg(not(feature = "luau"))]
+    pub(crate) unsafe fn remove_thread_hook(&self, thread_state: *mut ffi::lua_State) {
+        if (*self.extra.get()).interrupts.has_handles() {
+            ffi::lua_sethook(
+                thread_state,
+                Some(interrupt_hook_proc),
+                ffi::LUA_MASKCOUNT,
+                INTERRUPT_HOOK_COUNT,
+            );
+        } else {
+            ffi::lua_sethook(thread_state, None, 0, 0);
+        }
+    }
+
+    /// See [`Lua::interrupt_handle`] and [`Thread::interrupt_handle`]
+    pub(crate) unsafe fn interrupt_handle(
+        &self,
+        target: *mut ffi::lua_State,
+        pin: Option<RegistryKey>,
+    ) -> InterruptHandle {
+        let handle = (*self.extra.get()).interrupts.new_handle(target, pin);
+
+        #[cfg(feature = "luau")]
+        {
+            (*ffi::lua_callbacks(self.main_state())).interrupt = Some(Lua::interrupt_proc);
+        }
+
+        // Install the interrupt hook unless another hook is set (it checks interrupts too).
+        // New threads inherit the hook from the thread they were created in.
+        #[cfg(not(feature = "luau"))]
+        for state in [self.main_state(), self.state(), target] {
+            if !state.is_null() && ffi::lua_gethook(state).is_none() {
+                ffi::lua_sethook(
+                    state,
+                    Some(interrupt_hook_proc),
+                    ffi::LUA_MASKCOUNT,
+                    INTERRUPT_HOOK_COUNT,
+                );
+            }
+        }
+
+        handle
+    }
+
+    /// Detaches interrupt handles created by [`Thread::interrupt_handle`] from the thread.
+    pub(crate) unsafe fn detach_interrupt_handles(&self, thread_state: *mut ffi::lua_State) {
+        (*self.extra.get()).interrupts.detach(thread_state);
+    }
+
     /// See [`Lua::create_string`]
     pub(crate) unsafe fn create_string(&self, s: impl AsRef<[u8]>) -> Result<String> {
         let state = self.state();
@@ -1356,7 +1585,7 @@ impl RawLua {
         })
     }
 
-    unsafe fn make_userdata_with_metatable<T>(
+    unsafe fn make_userdata_with_metatable<T: 'static>(
         &self,
         data: UserDataStorage<T>,
         get_metatable_id: impl FnOnce() -> Result<Integer>,
@@ -1368,6 +1597,12 @@ impl RawLua {
         // We generate metatable first to make sure it *always* available when userdata pushed
         let mt_id = get_metatable_id()?;
         let protect = !self.unlikely_memory_error();
+        #[cfg(feature = "luau")]
+        match self.get_userdata_tag::<T>() {
+            Some(tag) => crate::util::push_tagged_userdata(state, data, tag, protect)?,
+            None => crate::util::push_userdata(state, data, protect)?,
+        };
+        #[cfg(not(feature = "luau"))]
         crate::util::push_userdata(state, data, protect)?;
         ffi::lua_rawgeti(state, ffi::LUA_REGISTRYINDEX, mt_id);
         ffi::lua_setmetatable(state, -2);
@@ -1581,6 +1816,34 @@ impl RawLua {
         Ok(())
     }
 
+    // Returns the Luau userdata tag assigned to the type `T`, allocating a new one if needed.
+    //
+    // Every allocated tag has a destructor for `UserDataStorage<T>` registered in the VM, so the
+    // tag is reserved for mlua and must not be used by foreign code to create userdata.
+    // Returns `None` if all tags are exhausted.
+    #[cfg(feature = "luau")]
+    unsafe fn get_userdata_tag<T: 'static>(&self) -> Option<c_int> {
+        let extra = &mut *self.extra.get();
+        let type_id = TypeId::of::<T>();
+        if let Some(&tag) = extra.registered_userdata_tags.get(&type_id) {
+            return Some(tag);
+        }
+
+        let state = self.main_state();
+        while extra.next_userdata_tag < ffi::LUA_UTAG_LIMIT {
+            let tag = extra.next_userdata_tag;
+            extra.next_userdata_tag += 1;
+            // Skip tags that are already used outside of mlua
+            if ffi::lua_getuserdatadtor(state, tag).is_some() {
+                continue;
+            }
+            ffi::lua_setuserdatadtor(state, tag, Some(collect_userdata::<UserDataStorage<T>>));
+            extra.registered_userdata_tags.insert(type_id, tag);
+            return Some(tag);
+        }
+        None
+    }
+
     #[inline(always)]
     pub(crate) unsafe fn register_userdata_metatable(&self, mt_ptr: *const c_void, type_id: Option<TypeId>) {
         (*self.extra.get()).registered_userdata_mt.insert(mt_ptr, type_id);
@@ -2050,3 +2313,39 @@ unsafe fn load_std_libs(state: *mut ffi::lua_State, libs: StdLib) -> Result<()>
 
     Ok(())
 }
+
+// Number of instructions between interrupt checks
+#[cfg(not(feature = "luau"))]
+const INTERRUPT_HOOK_COUNT: c_int = 1000;
+
+#[cfg(not(feature = "luau"))]
+unsafe extern "C-unwind" fn interrupt_hook_proc(state: *mut ffi::lua_State, _ar: *mut ffi::lua_Debug) {
+    let extra = ExtraData::get(state);
+    if extra.is_null() {
+        return;
+    }
+    let interrupted = (*extra).interrupts.is_pending() && (*extra).interrupts.check(state).is_err();
+    // Once interrupted, check on every instruction so the error escapes any `pcall` in the thread
+    let count = if interrupted { 1 } else { INTERRUPT_HOOK_COUNT };
+    if ffi::lua_gethookcount(state) != count {
+        ffi::lua_sethook(state, Some(interrupt_hook_proc), ffi::LUA_MASKCOUNT, count);
+    }
+    if interrupted {
+        callback_error_ext(state, extra, false, |_, _| Err::<(), _>(Error::Interrupted))
+    }
+}
+
+// Drops threads application data when the Lua state is being closed
+#[cfg(feature = "luau")]
+unsafe extern "C-unwind" fn close_thread_proc(parent: *mut ffi::lua_State, child: *mut ffi::lua_State) {
+    // It's not safe to unwind while Luau frees objects
+    unsafe extern "C" fn drop_app_data(data: *mut AppData) {
+        drop(Box::from_raw(data));
+    }
+
+    let data = ffi::lua_getthreaddata(child) as *mut AppData;
+    if parent.is_null() && !data.is_null() {
+        ffi::lua_setthreaddata(child, ptr::null_mut());
+        drop_app_data(data);
+    }
+}
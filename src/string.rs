@@ -1,16 +1,21 @@
 use std::borrow::{Borrow, Cow};
 use std::hash::{Hash, Hasher};
 use std::ops::Deref;
-use std::os::raw::{c_int, c_void};
+use std::os::raw::{c_char, c_int, c_void};
 use std::string::String as StdString;
-use std::{cmp, fmt, slice, str};
+use std::{cmp, fmt, io, mem, slice, str};
 
 use crate::error::{Error, Result};
-use crate::state::Lua;
-use crate::traits::IntoLua;
-use crate::types::{LuaType, ValueRef};
+use crate::state::{Lua, RawLua, StatePin, WeakLua};
+use crate::thread::Thread;
+use crate::traits::{FromLua, IntoLua};
+use crate::types::{LuaType, RegistryKey, ValueRef, XRc};
+use crate::util::{check_stack, StackGuard};
 use crate::value::Value;
 
+#[cfg(feature = "lua54")]
+use crate::util::pop_error;
+
 #[cfg(feature = "serde")]
 use {
     serde::ser::{Serialize, Serializer},
@@ -398,6 +403,390 @@ impl<'a> From<&'a String> for BorrowedBytes<'a> {
     }
 }
 
+/// An owned, reference-counted handle to the bytes of a Lua string.
+///
+/// Unlike [`BorrowedBytes`], this type does not borrow a [`String`] and is cheap to clone. The
+/// string is pinned in the Lua registry, so its data can be accessed without locking the Lua state
+/// and (with the `send` feature) moved to other threads without copying.
+///
+/// The handle does not keep the [`Lua`] instance alive. However, the underlying Lua state cannot
+/// be closed while the string data is referenced, so if the handle outlives the last [`Lua`]
+/// instance, the whole VM (not just the string) stays in memory until the last handle is dropped.
+/// The state is then closed on the thread dropping that handle, which runs any pending finalizers
+/// there. Copy the data (e.g. with `bytes.to_vec()`) if it must outlive the VM.
+///
+/// This struct is created by the [`String::to_lua_bytes`] method.
+#[derive(Clone)]
+pub struct LuaBytes(XRc<LuaBytesInner>);
+
+struct LuaBytesInner {
+    // `buf` points to a readonly memory managed by Lua, which is pinned by `key`
+    buf: *const u8,
+    len: usize,
+    key: RegistryKey,
+    // Postpones closing the Lua state until the handle is dropped
+    _pin: XRc<StatePin>,
+    _lua: WeakLua,
+}
+
+#[cfg(feature = "send")]
+unsafe impl Send for LuaBytesInner {}
+#[cfg(feature = "send")]
+unsafe impl Sync for LuaBytesInner {}
+
+impl String {
+    /// Returns an owned [`LuaBytes`] handle to the bytes of this string.
+    ///
+    /// The string data is not copied.
+    pub fn to_lua_bytes(&self) -> Result<LuaBytes> {
+        let (buf, lua) = unsafe { self.to_slice() };
+        let key = lua.create_registry_value(self)?;
+        let pin = XRc::clone(unsafe { &(*lua.lock().extra()).lua_bytes_pin });
+        Ok(LuaBytes(XRc::new(LuaBytesInner {
+            buf: buf.as_ptr(),
+            len: buf.len(),
+            key,
+            _pin: pin,
+            _lua: lua.weak(),
+        })))
+    }
+}
+
+impl LuaBytes {
+    /// Returns the bytes of the Lua string.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.0.buf, self.0.len) }
+    }
+}
+
+impl Deref for LuaBytes {
+    type Target = [u8];
+
+    #[inline(always)]
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl Borrow<[u8]> for LuaBytes {
+    #[inline(always)]
+    fn borrow(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsRef<[u8]> for LuaBytes {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl fmt::Debug for LuaBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LuaBytes({:?})", bstr::BStr::new(self.as_bytes()))
+    }
+}
+
+impl<T> PartialEq<T> for LuaBytes
+where
+    T: AsRef<[u8]>,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.as_bytes() == other.as_ref()
+    }
+}
+
+impl Eq for LuaBytes {}
+
+impl<T> PartialOrd<T> for LuaBytes
+where
+    T: AsRef<[u8]>,
+{
+    fn partial_cmp(&self, other: &T) -> Option<cmp::Ordering> {
+        self.as_bytes().partial_cmp(other.as_ref())
+    }
+}
+
+impl Ord for LuaBytes {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl Hash for LuaBytes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+impl<'a> IntoIterator for &'a LuaBytes {
+    type Item = &'a u8;
+    type IntoIter = slice::Iter<'a, u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromLua for LuaBytes {
+    #[inline]
+    fn from_lua(value: Value, lua: &Lua) -> Result<Self> {
+        String::from_lua(value, lua)?.to_lua_bytes()
+    }
+}
+
+impl IntoLua for LuaBytes {
+    #[inline]
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        lua.registry_value(&self.0.key)
+    }
+}
+
+impl IntoLua for &LuaBytes {
+    #[inline]
+    fn into_lua(self, lua: &Lua) -> Result<Value> {
+        lua.registry_value(&self.0.key)
+    }
+}
+
+#[cfg(all(feature = "bytes", feature = "send"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+impl From<LuaBytes> for bytes::Bytes {
+    /// Converts [`LuaBytes`] into [`bytes::Bytes`] without copying the data.
+    fn from(value: LuaBytes) -> Self {
+        bytes::Bytes::from_owner(value)
+    }
+}
+
+#[cfg(all(feature = "bytes", not(feature = "send")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+impl From<LuaBytes> for bytes::Bytes {
+    /// Converts [`LuaBytes`] into [`bytes::Bytes`].
+    ///
+    /// The data is copied, as [`bytes::Bytes`] requires its owner to be `Send` (enable the `send`
+    /// feature to avoid copying).
+    fn from(value: LuaBytes) -> Self {
+        bytes::Bytes::copy_from_slice(&value)
+    }
+}
+
+/// An incremental builder of Lua strings.
+///
+/// The builder is backed by the Lua auxiliary library buffer (`luaL_Buffer`), so data is
+/// accumulated inside the Lua VM and turned into a Lua [`String`] without assembling an
+/// intermediate Rust buffer first.
+///
+/// Implements [`std::fmt::Write`] and [`std::io::Write`] traits.
+///
+/// This struct is created by the [`Lua::create_string_builder`] method.
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{Lua, Result};
+/// # fn main() -> Result<()> {
+/// # let lua = Lua::new();
+/// use std::fmt::Write;
+///
+/// let mut builder = lua.create_string_builder()?;
+/// builder.push("hello")?;
+/// write!(builder, ", {}!", "world").unwrap();
+/// assert_eq!(builder.finish()?, "hello, world!");
+/// # Ok(())
+/// # }
+/// ```
+pub struct StringBuilder {
+    // Dedicated thread which stack is used exclusively by the buffer
+    thread: Thread,
+    // `luaL_Buffer` must not move as it can hold pointers to itself
+    buf: Box<ffi::luaL_Buffer>,
+    len: usize,
+    poisoned: bool,
+}
+
+#[cfg(feature = "send")]
+unsafe impl Send for StringBuilder {}
+#[cfg(feature = "send")]
+unsafe impl Sync for StringBuilder {}
+
+impl StringBuilder {
+    pub(crate) unsafe fn new(lua: &RawLua) -> Result<Self> {
+        let state = lua.state();
+        let _sg = StackGuard::new(state);
+        check_stack(state, 3)?;
+
+        let thread_state = protect_lua!(state, 0, 1, |state| ffi::lua_newthread(state))?;
+        let mut builder = StringBuilder {
+            thread: Thread(lua.pop_ref(), thread_state),
+            buf: Box::new(mem::zeroed()),
+            len: 0,
+            poisoned: false,
+        };
+        #[cfg(not(feature = "lua54"))]
+        builder.run(|buf| ffi::luaL_buffinit(thread_state, buf))?;
+        #[cfg(feature = "lua54")]
+        {
+            check_stack(thread_state, 2)?;
+            ffi::lua_pushcfunction(thread_state, builder_proc);
+            ffi::lua_pushlightuserdata(
+                thread_state,
+                &mut *builder.buf as *mut ffi::luaL_Buffer as *mut c_void,
+            );
+            builder.resume(1)?;
+        }
+        Ok(builder)
+    }
+
+    /// Appends the given bytes to the builder.
+    pub fn push(&mut self, data: impl AsRef<[u8]>) -> Result<()> {
+        let data = data.as_ref();
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let _lua = self.thread.0.lua.lock();
+        #[cfg(not(feature = "lua54"))]
+        unsafe {
+            self.run(|buf| ffi::luaL_addlstring(buf, data.as_ptr() as *const c_char, data.len()))?
+        };
+        #[cfg(feature = "lua54")]
+        unsafe {
+            let thread_state = self.thread.1;
+            check_stack(thread_state, 2)?;
+            ffi::lua_pushlightuserdata(thread_state, data.as_ptr() as *mut c_void);
+            ffi::lua_pushinteger(thread_state, data.len() as ffi::lua_Integer);
+            self.resume(2)?;
+        }
+        self.len += data.len();
+        Ok(())
+    }
+
+    /// Returns the number of bytes appended to the builder so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if nothing has been appended to the builder yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Consumes the builder, returning the resulting Lua string.
+    pub fn finish(mut self) -> Result<String> {
+        let lua = self.thread.0.lua.lock();
+        unsafe {
+            #[cfg(not(feature = "lua54"))]
+            self.run(|buf| ffi::luaL_pushresult(buf))?;
+            #[cfg(feature = "lua54")]
+            self.resume(0)?;
+            Ok(String(lua.pop_ref_at(self.thread.1)))
+        }
+    }
+
+    // Runs the given buffer operation in protected mode.
+    //
+    // All values owned by the buffer are passed as arguments to the protected call and returned
+    // back, so from the buffer's point of view the stack is never disturbed.
+    // The Lua state must be locked.
+    #[cfg(not(feature = "lua54"))]
+    unsafe fn run(&mut self, f: impl FnOnce(*mut ffi::luaL_Buffer)) -> Result<()> {
+        if self.poisoned {
+            return Err(Error::runtime("string builder cannot be used after an error"));
+        }
+
+        let state = self.thread.1;
+        check_stack(state, 3)?;
+        let buf = &mut *self.buf as *mut ffi::luaL_Buffer;
+        let nargs = ffi::lua_gettop(state);
+        let result = protect_lua!(state, nargs, ffi::LUA_MULTRET, |_| f(buf));
+        // On error the buffer values are lost, so the builder cannot be used anymore
+        self.poisoned = result.is_err();
+        result
+    }
+
+    // Resumes the buffer coroutine with `nargs` arguments already pushed to the thread stack.
+    //
+    // On Lua 5.4 the buffer keeps its storage in a to-be-closed slot of the C frame it was grown
+    // in, so all buffer operations run inside a single C function (see `builder_proc`) that yields
+    // between them.
+    #[cfg(feature = "lua54")]
+    unsafe fn resume(&mut self, nargs: c_int) -> Result<()> {
+        let lua = self.thread.0.lua.lock();
+        let state = self.thread.1;
+        if self.poisoned {
+            ffi::lua_pop(state, nargs);
+            return Err(Error::runtime("string builder cannot be used after an error"));
+        }
+
+        let mut nresults = 0;
+        match ffi::lua_resume(state, lua.state(), nargs, &mut nresults) {
+            ffi::LUA_OK | ffi::LUA_YIELD => Ok(()),
+            status => {
+                // The coroutine is dead, so the builder cannot be used anymore
+                self.poisoned = true;
+                Err(pop_error(state, status))
+            }
+        }
+    }
+}
+
+// Body of the buffer coroutine on Lua 5.4.
+//
+// Receives a pointer to `luaL_Buffer`, initializes it and then yields waiting for data to append
+// (pointer and length) or for no arguments to push the resulting string and return it.
+#[cfg(feature = "lua54")]
+unsafe extern "C-unwind" fn builder_proc(state: *mut ffi::lua_State) -> c_int {
+    let buf = ffi::lua_touserdata(state, 1) as *mut ffi::luaL_Buffer;
+    ffi::lua_pop(state, 1);
+    ffi::luaL_buffinit(state, buf);
+    ffi::lua_yieldk(state, 0, buf as ffi::lua_KContext, Some(builder_cont))
+}
+
+#[cfg(feature = "lua54")]
+unsafe extern "C-unwind" fn builder_cont(
+    state: *mut ffi::lua_State,
+    _status: c_int,
+    ctx: ffi::lua_KContext,
+) -> c_int {
+    let buf = ctx as *mut ffi::luaL_Buffer;
+    // The buffer occupies the first stack slot, resume arguments (if any) are pushed on top of it
+    // and must be popped before using the buffer
+    if ffi::lua_gettop(state) > 1 {
+        let len = ffi::lua_tointeger(state, -1) as usize;
+        let data = ffi::lua_touserdata(state, -2) as *const c_char;
+        ffi::lua_pop(state, 2);
+        ffi::luaL_addlstring(buf, data, len);
+        return ffi::lua_yieldk(state, 0, ctx, Some(builder_cont));
+    }
+    ffi::luaL_pushresult(buf);
+    1
+}
+
+impl fmt::Debug for StringBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StringBuilder").field("len", &self.len).finish()
+    }
+}
+
+impl fmt::Write for StringBuilder {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl io::Write for StringBuilder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.push(buf).map_err(|err| io::Error::other(err.to_string()))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 struct WrappedString<T: AsRef<[u8]>>(T);
 
 impl String {
@@ -431,4 +820,12 @@ mod assertions {
     static_assertions::assert_impl_all!(BorrowedBytes: Send, Sync);
     #[cfg(feature = "send")]
     static_assertions::assert_impl_all!(BorrowedStr: Send, Sync);
+    #[cfg(not(feature = "send"))]
+    static_assertions::assert_not_impl_any!(StringBuilder: Send);
+    #[cfg(not(feature = "send"))]
+    static_assertions::assert_not_impl_any!(LuaBytes: Send);
+    #[cfg(feature = "send")]
+    static_assertions::assert_impl_all!(StringBuilder: Send, Sync);
+    #[cfg(feature = "send")]
+    static_assertions::assert_impl_all!(LuaBytes: Send, Sync);
 }
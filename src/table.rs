@@ -1,24 +1,30 @@
+use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::fmt;
 use std::marker::PhantomData;
 use std::os::raw::{c_int, c_void};
+use std::result::Result as StdResult;
 use std::string::String as StdString;
 
 use crate::error::{Error, Result};
 use crate::function::Function;
 use crate::state::{LuaGuard, RawLua};
 use crate::traits::{FromLua, FromLuaMulti, IntoLua, IntoLuaMulti, ObjectLike};
-use crate::types::{Integer, LuaType, ValueRef};
-use crate::util::{assert_stack, check_stack, get_metatable_ptr, StackGuard};
+use crate::types::{Integer, LuaType, RegistryKey, ValueRef};
+use crate::util::{assert_stack, check_stack, get_metatable_ptr, push_table, StackGuard};
 use crate::value::{Nil, Value};
 
 #[cfg(feature = "serde")]
 use {
     rustc_hash::FxHashSet,
     serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer},
-    std::{cell::RefCell, rc::Rc, result::Result as StdResult},
+    std::{cell::RefCell, rc::Rc},
 };
 
+pub use schema::{FieldType, TableSchema, Typed, TypedTable};
+
+mod schema;
+
 /// Handle to an internal Lua table.
 #[derive(Clone, PartialEq)]
 pub struct Table(pub(crate) ValueRef);
@@ -647,6 +653,38 @@ impl Table {
         Ok(())
     }
 
+    /// Returns a cursor for paging through the pairs of the table.
+    ///
+    /// Unlike [`Table::pairs`], the cursor does not hold the Lua state locked between batches.
+    /// The last visited key is kept in the Lua registry, so iteration can be resumed later (for
+    /// example from another callback). It does not invoke the `__pairs` metamethod.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let table = lua.create_sequence_from(1..=10)?;
+    /// let mut cursor = table.cursor();
+    /// let mut sum = 0;
+    /// while !cursor.is_finished() {
+    ///     for (_, v) in cursor.next_batch::<i64, i64>(3)? {
+    ///         sum += v;
+    ///     }
+    /// }
+    /// assert_eq!(sum, 55);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cursor(&self) -> TableCursor {
+        TableCursor {
+            table: self.clone(),
+            key: None,
+            finished: false,
+        }
+    }
+
     /// Returns an iterator over all values in the sequence part of the table.
     ///
     /// The iterator will yield all values `t[1]`, `t[2]` and so on, until a `nil` value is
@@ -707,6 +745,382 @@ impl Table {
         Ok(())
     }
 
+    /// Sets multiple key-value pairs at once.
+    ///
+    /// This is equivalent to calling [`Table::set`] for each pair, but the Lua state is locked and
+    /// the table is pushed to the stack only once. This might invoke the `__newindex` metamethod.
+    ///
+    /// Lua has no API to grow an existing table, so the table is not pre-sized and may be rehashed
+    /// several times while the pairs are inserted. When filling a new table, create it with
+    /// [`Lua::create_table_with_capacity`] (or use [`Lua::create_table_from`]) instead.
+    ///
+    /// [`Lua::create_table_with_capacity`]: crate::Lua::create_table_with_capacity
+    /// [`Lua::create_table_from`]: crate::Lua::create_table_from
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let table = lua.create_table_with_capacity(0, 2)?;
+    /// table.set_many([("a", 1), ("b", 2)])?;
+    /// assert_eq!(table.get::<i32>("b")?, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_many<K, V>(&self, iter: impl IntoIterator<Item = (K, V)>) -> Result<()>
+    where
+        K: IntoLua,
+        V: IntoLua,
+    {
+        let has_metatable = self.has_metatable();
+        let lua = self.0.lua.lock();
+        let state = lua.state();
+        unsafe {
+            #[cfg(feature = "luau")]
+            self.check_readonly_write(&lua)?;
+
+            let _sg = StackGuard::new(state);
+            check_stack(state, 5)?;
+
+            lua.push_ref_at(&self.0, state);
+            let protect = !lua.unlikely_memory_error();
+            for (key, value) in iter {
+                key.push_into_specified_stack(&lua, state)?;
+                value.push_into_specified_stack(&lua, state)?;
+                if has_metatable {
+                    protect_lua!(state, 3, 1, fn(state) ffi::lua_settable(state, -3))?;
+                } else if protect {
+                    protect_lua!(state, 3, 1, fn(state) ffi::lua_rawset(state, -3))?;
+                } else {
+                    ffi::lua_rawset(state, -3);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the values associated to multiple keys at once.
+    ///
+    /// This is equivalent to calling [`Table::get`] for each key, but the Lua state is locked and
+    /// the table is pushed to the stack only once. This might invoke the `__index` metamethod.
+    pub fn get_many<K, V>(&self, keys: impl IntoIterator<Item = K>) -> Result<Vec<V>>
+    where
+        K: IntoLua,
+        V: FromLua,
+    {
+        let has_metatable = self.has_metatable();
+        let lua = self.0.lua.lock();
+        let state = lua.state();
+        let keys = keys.into_iter();
+        let mut values = Vec::with_capacity(keys.size_hint().0);
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 5)?;
+
+            lua.push_ref_at(&self.0, state);
+            for key in keys {
+                key.push_into_specified_stack(&lua, state)?;
+                if has_metatable {
+                    protect_lua!(state, 2, 2, fn(state) ffi::lua_gettable(state, -2))?;
+                } else {
+                    ffi::lua_rawget(state, -2);
+                }
+                values.push(V::from_specified_stack(-1, &lua, state)?);
+                ffi::lua_pop(state, 1);
+            }
+        }
+        Ok(values)
+    }
+
+    /// Appends all values from the iterator to the back of the table without invoking
+    /// metamethods.
+    ///
+    /// This is equivalent to calling [`Table::raw_push`] for each value, but the Lua state is
+    /// locked and the table is pushed to the stack only once.
+    ///
+    /// Lua has no API to grow an existing table, so the array part is not pre-sized and may be
+    /// reallocated several times. When filling a new table, create it with
+    /// [`Lua::create_table_with_capacity`] (or use [`Lua::create_sequence_from`]) instead.
+    ///
+    /// [`Lua::create_table_with_capacity`]: crate::Lua::create_table_with_capacity
+    /// [`Lua::create_sequence_from`]: crate::Lua::create_sequence_from
+    pub fn extend_sequence<V: IntoLua>(&self, iter: impl IntoIterator<Item = V>) -> Result<()> {
+        let lua = self.0.lua.lock();
+        let state = lua.state();
+        unsafe {
+            #[cfg(feature = "luau")]
+            self.check_readonly_write(&lua)?;
+
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_ref_at(&self.0, state);
+            let protect = !lua.unlikely_memory_error();
+            let mut idx = ffi::lua_rawlen(state, -1) as Integer;
+            for value in iter {
+                idx += 1;
+                value.push_into_specified_stack(&lua, state)?;
+                if protect {
+                    protect_lua!(state, 2, 1, |state| ffi::lua_rawseti(state, -2, idx))?;
+                } else {
+                    ffi::lua_rawseti(state, -2, idx);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Collects the sequence part of the table into a [`Vec`], without invoking metamethods.
+    ///
+    /// Unlike [`Table::sequence_values`], the length of the sequence is determined once (as the
+    /// `#` operator without `__len` would) and the vector is allocated upfront.
+    pub fn to_vec<V: FromLua>(&self) -> Result<Vec<V>> {
+        let lua = self.0.lua.lock();
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_ref_at(&self.0, state);
+            let len = ffi::lua_rawlen(state, -1);
+            let mut values = Vec::with_capacity(len);
+            for i in 1..=len {
+                ffi::lua_rawgeti(state, -1, i as _);
+                values.push(V::from_specified_stack(-1, &lua, state)?);
+                ffi::lua_pop(state, 1);
+            }
+            Ok(values)
+        }
+    }
+
+    /// Sorts the sequence part of the table in place, without invoking metamethods.
+    ///
+    /// Values are ordered by type first (nil, booleans, numbers, strings, then other values by
+    /// their address) and then by value within the same type. Integers and floats are compared
+    /// numerically.
+    ///
+    /// The sort is stable.
+    pub fn sort(&self) -> Result<()> {
+        self.sort_by(Value::sort_cmp)
+    }
+
+    /// Sorts the sequence part of the table in place with a comparator function, without
+    /// invoking metamethods.
+    ///
+    /// The sort is stable. Values are merged through a temporary Lua table of the same length
+    /// rather than copied into Rust, so only the two values being compared are held as [`Value`]s
+    /// at any time.
+    ///
+    /// The comparator must not modify the table, otherwise the resulting order is unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let table = lua.create_sequence_from([3, 1, 2])?;
+    /// table.sort_by(|a, b| b.as_integer().cmp(&a.as_integer()))?;
+    /// assert_eq!(table.to_vec::<i32>()?, vec![3, 2, 1]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sort_by(&self, mut compare: impl FnMut(&Value, &Value) -> Ordering) -> Result<()> {
+        let lua = self.0.lua.lock();
+        let state = lua.state();
+        unsafe {
+            #[cfg(feature = "luau")]
+            self.check_readonly_write(&lua)?;
+
+            let _sg = StackGuard::new(state);
+            check_stack(state, 8)?;
+
+            lua.push_ref_at(&self.0, state);
+            let len = ffi::lua_rawlen(state, -1);
+            if len < 2 {
+                return Ok(());
+            }
+            let protect = !lua.unlikely_memory_error();
+            push_table(state, len, 0, protect)?;
+            let (table, aux) = (ffi::lua_absindex(state, -2), ffi::lua_absindex(state, -1));
+
+            // Bottom-up merge sort, alternating between the table and the auxiliary table.
+            // Only the heads of the two merged runs are held as `Value`s at a time.
+            let get = |t: c_int, i: usize| {
+                ffi::lua_rawgeti(state, t, i as _);
+                lua.pop_value_at(state)
+            };
+            let set = |t: c_int, i: usize, value: &Value| -> Result<()> {
+                ffi::lua_pushvalue(state, t);
+                lua.push_value_at(value, state)?;
+                if protect {
+                    protect_lua!(state, 2, 0, |state| ffi::lua_rawseti(state, -2, i as _))?;
+                } else {
+                    ffi::lua_rawseti(state, -2, i as _);
+                    ffi::lua_pop(state, 1);
+                }
+                Ok(())
+            };
+
+            let (mut src, mut dst) = (table, aux);
+            let mut width = 1;
+            while width < len {
+                let mut lo = 1;
+                while lo <= len {
+                    let mid = (lo + width).min(len + 1);
+                    let hi = (lo + 2 * width).min(len + 1);
+                    let (mut i, mut j) = (lo, mid);
+                    let mut left = if i < mid { Some(get(src, i)?) } else { None };
+                    let mut right = if j < hi { Some(get(src, j)?) } else { None };
+                    for k in lo..hi {
+                        let take_left = match (&left, &right) {
+                            (Some(l), Some(r)) => compare(l, r) != Ordering::Greater,
+                            (l, _) => l.is_some(),
+                        };
+                        if take_left {
+                            set(dst, k, left.as_ref().unwrap())?;
+                            i += 1;
+                            left = if i < mid { Some(get(src, i)?) } else { None };
+                        } else {
+                            set(dst, k, right.as_ref().unwrap())?;
+                            j += 1;
+                            right = if j < hi { Some(get(src, j)?) } else { None };
+                        }
+                    }
+                    lo = hi;
+                }
+                (src, dst) = (dst, src);
+                width *= 2;
+            }
+
+            // The sorted values ended up in the auxiliary table
+            if src == aux {
+                for i in 1..=len {
+                    let value = get(aux, i)?;
+                    set(table, i, &value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Retains only the values in the sequence part of the table for which the predicate returns
+    /// `true`, without invoking metamethods.
+    ///
+    /// The remaining values are shifted down to keep the sequence contiguous, preserving their
+    /// order.
+    ///
+    /// If the predicate returns an error, the values that have not been checked yet are retained
+    /// and the error is returned.
+    pub fn retain<V: FromLua>(&self, mut f: impl FnMut(V) -> Result<bool>) -> Result<()> {
+        let lua = self.0.lua.lock();
+        let state = lua.state();
+        unsafe {
+            #[cfg(feature = "luau")]
+            self.check_readonly_write(&lua)?;
+
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_ref_at(&self.0, state);
+            let len = ffi::lua_rawlen(state, -1) as Integer;
+            let mut new_len = 0;
+            let mut result = Ok(());
+            for i in 1..=len {
+                ffi::lua_rawgeti(state, -1, i);
+                // Keep compacting the sequence after an error
+                let keep = result.is_err() || {
+                    ffi::lua_pushvalue(state, -1);
+                    let keep = V::from_specified_stack(-1, &lua, state).and_then(&mut f);
+                    ffi::lua_pop(state, 1);
+                    keep.unwrap_or_else(|err| {
+                        result = Err(err);
+                        true
+                    })
+                };
+                if keep {
+                    new_len += 1;
+                    ffi::lua_rawseti(state, -2, new_len);
+                } else {
+                    ffi::lua_pop(state, 1);
+                }
+            }
+            for i in new_len + 1..=len {
+                ffi::lua_pushnil(state);
+                ffi::lua_rawseti(state, -2, i);
+            }
+            result
+        }
+    }
+
+    /// Replaces each value in the sequence part of the table with the result of the given
+    /// closure, without invoking metamethods.
+    ///
+    /// Returning `nil` from the closure is not allowed as it would break the sequence.
+    pub fn map_in_place<V, R>(&self, mut f: impl FnMut(V) -> Result<R>) -> Result<()>
+    where
+        V: FromLua,
+        R: IntoLua,
+    {
+        let lua = self.0.lua.lock();
+        let state = lua.state();
+        unsafe {
+            #[cfg(feature = "luau")]
+            self.check_readonly_write(&lua)?;
+
+            let _sg = StackGuard::new(state);
+            check_stack(state, 4)?;
+
+            lua.push_ref_at(&self.0, state);
+            let len = ffi::lua_rawlen(state, -1) as Integer;
+            for i in 1..=len {
+                ffi::lua_rawgeti(state, -1, i);
+                let value = f(V::from_specified_stack(-1, &lua, state)?)?;
+                ffi::lua_pop(state, 1);
+                value.push_into_specified_stack(&lua, state)?;
+                if ffi::lua_type(state, -1) == ffi::LUA_TNIL {
+                    return Err(Error::runtime("cannot assign nil to a sequence element"));
+                }
+                ffi::lua_rawseti(state, -2, i);
+            }
+        }
+        Ok(())
+    }
+
+    /// Binary searches the sorted sequence part of the table with a comparator function,
+    /// without invoking metamethods.
+    ///
+    /// The comparator should return an order code that indicates whether its argument is
+    /// `Less`, `Equal` or `Greater` than the desired target.
+    ///
+    /// If a matching value is found, returns `Ok` with its (1-based) Lua index. Otherwise returns
+    /// `Err` with the Lua index where a matching value could be inserted while maintaining the
+    /// sorted order. See [`slice::binary_search_by`] for details.
+    pub fn binary_search_by(&self, mut f: impl FnMut(&Value) -> Ordering) -> Result<StdResult<usize, usize>> {
+        let lua = self.0.lua.lock();
+        let state = lua.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 3)?;
+
+            lua.push_ref_at(&self.0, state);
+            let (mut left, mut right) = (0, ffi::lua_rawlen(state, -1));
+            while left < right {
+                let mid = left + (right - left) / 2;
+                ffi::lua_rawgeti(state, -1, (mid + 1) as Integer);
+                let value = lua.pop_value_at(state)?;
+                match f(&value) {
+                    Ordering::Less => left = mid + 1,
+                    Ordering::Greater => right = mid,
+                    Ordering::Equal => return Ok(Ok(mid + 1)),
+                }
+            }
+            Ok(Err(left + 1))
+        }
+    }
+
     /// Sets element value at position `idx` without invoking metamethods.
     #[doc(hidden)]
     pub fn raw_seti(&self, idx: usize, value: impl IntoLua) -> Result<()> {
@@ -1092,6 +1506,95 @@ where
     }
 }
 
+/// A resumable cursor over the pairs of a Lua table.
+///
+/// This struct is created by the [`Table::cursor`] method.
+///
+/// Values may be assigned (or set to `nil`) in the table between batches. If the last visited key
+/// is removed from the table, the next batch fails with an error instead of resuming from an
+/// undefined position. Keys added between batches may or may not be visited.
+#[derive(Debug)]
+pub struct TableCursor {
+    table: Table,
+    key: Option<RegistryKey>,
+    finished: bool,
+}
+
+impl TableCursor {
+    /// Returns up to `limit` next pairs of the table.
+    ///
+    /// Returns an empty vector once the iteration is finished.
+    pub fn next_batch<K: FromLua, V: FromLua>(&mut self, limit: usize) -> Result<Vec<(K, V)>> {
+        if self.finished || limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut items = Vec::new();
+        let lua = self.table.0.lua.lock();
+        let state = lua.state();
+        let last_key = unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, 5)?;
+
+            lua.push_ref_at(&self.table.0, state);
+            match &self.key {
+                Some(key) => {
+                    key.push_into_specified_stack(&lua, state)?;
+                    // Calling `lua_next` with a key that is no longer in the table raises an error
+                    ffi::lua_pushvalue(state, -1);
+                    ffi::lua_rawget(state, -3);
+                    if ffi::lua_type(state, -1) == ffi::LUA_TNIL {
+                        return Err(Error::runtime(
+                            "table was modified during iteration (key removed)",
+                        ));
+                    }
+                    ffi::lua_pop(state, 1);
+                }
+                None => ffi::lua_pushnil(state),
+            }
+
+            while items.len() < limit {
+                if ffi::lua_next(state, -2) == 0 {
+                    self.finished = true;
+                    break;
+                }
+                let key = lua.stack_value_at(-2, None, state)?;
+                let value = V::from_specified_stack(-1, &lua, state)?;
+                items.push((K::from_lua(key, lua.lua())?, value));
+                ffi::lua_pop(state, 1);
+            }
+
+            match self.finished {
+                true => None,
+                false => Some(lua.stack_value_at(-1, None, state)?),
+            }
+        };
+        drop(lua);
+
+        self.key = match last_key {
+            Some(key) => Some(self.table.0.lua.upgrade().create_registry_value(key)?),
+            None => None,
+        };
+        Ok(items)
+    }
+
+    /// Returns `true` if all pairs of the table have been visited.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Restarts the iteration from the beginning of the table.
+    pub fn reset(&mut self) {
+        self.key = None;
+        self.finished = false;
+    }
+
+    /// Returns the table this cursor iterates over.
+    pub fn table(&self) -> &Table {
+        &self.table
+    }
+}
+
 /// An iterator over the sequence part of a Lua table.
 ///
 /// This struct is created by the [`Table::sequence_values`] method.
@@ -1139,4 +1642,6 @@ mod assertions {
     static_assertions::assert_not_impl_any!(Table: Send);
     #[cfg(feature = "send")]
     static_assertions::assert_impl_all!(Table: Send, Sync);
+    #[cfg(feature = "send")]
+    static_assertions::assert_impl_all!(TableCursor: Send, Sync);
 }
@@ -0,0 +1,412 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::string::String as StdString;
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::state::Lua;
+use crate::table::Table;
+use crate::traits::{FromLua, IntoLua, ShortTypeName as _};
+use crate::util::{join_path, PathSegment};
+use crate::value::Value;
+
+type DefaultFn = Arc<dyn Fn(&Lua) -> Result<Value> + Send + Sync>;
+type CheckFn = Arc<dyn Fn(&Value) -> StdResult<(), StdString> + Send + Sync>;
+type StdResult<T, E> = std::result::Result<T, E>;
+
+/// Expected type of a value checked by [`TableSchema`].
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum FieldType {
+    /// Any non-nil value.
+    Any,
+    /// A boolean.
+    Boolean,
+    /// An integer, or a float with an exact integer representation.
+    Integer,
+    /// An integer or a float.
+    Number,
+    /// A string.
+    String,
+    /// A function.
+    Function,
+    /// A table of any shape.
+    Table,
+    /// A userdata.
+    UserData,
+    /// A sequence whose elements all match the inner type.
+    Array(Box<FieldType>),
+    /// A table whose keys and values all match the inner types.
+    Map(Box<FieldType>, Box<FieldType>),
+    /// A table matching a nested schema.
+    Schema(TableSchema),
+    /// A value accepted by a custom check.
+    ///
+    /// The check returns an error message if the value is invalid.
+    Custom(CheckFn),
+}
+
+impl FieldType {
+    /// Creates [`FieldType::Array`] with the given element type.
+    pub fn array(elem: FieldType) -> Self {
+        FieldType::Array(Box::new(elem))
+    }
+
+    /// Creates [`FieldType::Map`] with the given key and value types.
+    pub fn map(key: FieldType, value: FieldType) -> Self {
+        FieldType::Map(Box::new(key), Box::new(value))
+    }
+
+    /// Creates [`FieldType::Custom`] from a check function.
+    pub fn custom<F>(check: F) -> Self
+    where
+        F: Fn(&Value) -> StdResult<(), StdString> + Send + Sync + 'static,
+    {
+        FieldType::Custom(Arc::new(check))
+    }
+
+    fn check(&self, value: &Value, path: &Path, fill_defaults: bool) -> Result<()> {
+        let expected = match (self, value) {
+            (FieldType::Any, v) if !v.is_nil() => return Ok(()),
+            (FieldType::Boolean, Value::Boolean(_)) => return Ok(()),
+            (FieldType::Integer, Value::Integer(_)) => return Ok(()),
+            (FieldType::Integer, Value::Number(n)) if n.fract() == 0.0 => return Ok(()),
+            (FieldType::Number, Value::Integer(_) | Value::Number(_)) => return Ok(()),
+            (FieldType::String, Value::String(_)) => return Ok(()),
+            (FieldType::Function, Value::Function(_)) => return Ok(()),
+            (FieldType::Table, Value::Table(_)) => return Ok(()),
+            (FieldType::UserData, Value::UserData(_)) => return Ok(()),
+            (FieldType::Array(elem), Value::Table(t)) => {
+                let len = t.raw_len();
+                for i in 1..=len {
+                    elem.check(&t.raw_get::<Value>(i)?, &path.index(i), fill_defaults)?;
+                }
+                return Ok(());
+            }
+            (FieldType::Map(key_ty, value_ty), Value::Table(t)) => {
+                for pair in t.pairs::<Value, Value>() {
+                    let (k, v) = pair?;
+                    let path = path.key(&k);
+                    key_ty.check(&k, &path, fill_defaults)?;
+                    value_ty.check(&v, &path, fill_defaults)?;
+                }
+                return Ok(());
+            }
+            (FieldType::Schema(schema), Value::Table(t)) => return schema.check(t, path, fill_defaults),
+            (FieldType::Custom(check), v) => {
+                return check(v).map_err(|message| path.error(message));
+            }
+            (FieldType::Any, _) => "any value",
+            (FieldType::Boolean, _) => "boolean",
+            (FieldType::Integer, _) => "integer",
+            (FieldType::Number, _) => "number",
+            (FieldType::String, _) => "string",
+            (FieldType::Function, _) => "function",
+            (FieldType::Table | FieldType::Map(..) | FieldType::Schema(_), _) => "table",
+            (FieldType::Array(_), _) => "array",
+            (FieldType::UserData, _) => "userdata",
+        };
+        Err(path.error(format!("expected {expected}, got {}", value.type_name())))
+    }
+}
+
+impl fmt::Debug for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldType::Any => write!(f, "Any"),
+            FieldType::Boolean => write!(f, "Boolean"),
+            FieldType::Integer => write!(f, "Integer"),
+            FieldType::Number => write!(f, "Number"),
+            FieldType::String => write!(f, "String"),
+            FieldType::Function => write!(f, "Function"),
+            FieldType::Table => write!(f, "Table"),
+            FieldType::UserData => write!(f, "UserData"),
+            FieldType::Array(elem) => f.debug_tuple("Array").field(elem).finish(),
+            FieldType::Map(key, value) => f.debug_tuple("Map").field(key).field(value).finish(),
+            FieldType::Schema(schema) => f.debug_tuple("Schema").field(schema).finish(),
+            FieldType::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Field {
+    name: StdString,
+    ty: FieldType,
+    required: bool,
+    default: Option<DefaultFn>,
+}
+
+/// A declarative description of the expected shape of a Lua table.
+///
+/// A schema is a list of named fields, each with an expected [`FieldType`] and a flag whether
+/// the field is required, optional or has a default value. Nested tables are described with
+/// [`FieldType::Schema`], [`FieldType::Array`] or [`FieldType::Map`].
+///
+/// Validation errors are reported as [`Error::SchemaError`] carrying the path to the offending
+/// value, for example `servers[2].port`.
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{FieldType, Lua, Result, Table, TableSchema};
+/// # fn main() -> Result<()> {
+/// # let lua = Lua::new();
+/// let schema = TableSchema::new()
+///     .field("name", FieldType::String)
+///     .field_with_default("port", FieldType::Integer, 8080)
+///     .optional_field("tags", FieldType::array(FieldType::String));
+///
+/// let config: Table = lua.load(r#"{name = "server", tags = {"a", "b"}}"#).eval()?;
+/// schema.apply(&config)?;
+/// assert_eq!(config.get::<u16>("port")?, 8080);
+///
+/// let config: Table = lua.load(r#"{name = "server", tags = {"a", 1}}"#).eval()?;
+/// let err = schema.validate(&config).unwrap_err();
+/// assert_eq!(err.to_string(), "schema error at `tags[2]`: expected string, got integer");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct TableSchema {
+    fields: Vec<Field>,
+    deny_unknown_fields: bool,
+}
+
+impl TableSchema {
+    /// Creates a new empty schema.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a required field.
+    #[must_use]
+    pub fn field(mut self, name: impl Into<StdString>, ty: FieldType) -> Self {
+        self.push_field(name.into(), ty, true, None);
+        self
+    }
+
+    /// Adds an optional field.
+    ///
+    /// The field may be missing (nil), otherwise it must match `ty`.
+    #[must_use]
+    pub fn optional_field(mut self, name: impl Into<StdString>, ty: FieldType) -> Self {
+        self.push_field(name.into(), ty, false, None);
+        self
+    }
+
+    /// Adds an optional field with a default value.
+    ///
+    /// The default value is written to the table by [`TableSchema::apply`] when the field is
+    /// missing.
+    #[must_use]
+    pub fn field_with_default<V>(mut self, name: impl Into<StdString>, ty: FieldType, default: V) -> Self
+    where
+        V: IntoLua + Clone + Send + Sync + 'static,
+    {
+        let default: DefaultFn = Arc::new(move |lua| default.clone().into_lua(lua));
+        self.push_field(name.into(), ty, false, Some(default));
+        self
+    }
+
+    /// Adds a required field that must match a nested schema.
+    #[must_use]
+    pub fn nested(self, name: impl Into<StdString>, schema: TableSchema) -> Self {
+        self.field(name, FieldType::Schema(schema))
+    }
+
+    /// If true, fields that are not declared in the schema are reported as errors.
+    ///
+    /// Default: **false**
+    #[must_use]
+    pub fn deny_unknown_fields(mut self, enabled: bool) -> Self {
+        self.deny_unknown_fields = enabled;
+        self
+    }
+
+    /// Checks the table against this schema without modifying it.
+    pub fn validate(&self, table: &Table) -> Result<()> {
+        self.check(table, &Path::root(), false)
+    }
+
+    /// Checks the table against this schema, filling in default values for missing fields
+    /// (including in nested tables).
+    pub fn apply(&self, table: &Table) -> Result<()> {
+        self.check(table, &Path::root(), true)
+    }
+
+    fn push_field(&mut self, name: StdString, ty: FieldType, required: bool, default: Option<DefaultFn>) {
+        let field = Field {
+            name,
+            ty,
+            required,
+            default,
+        };
+        match self.fields.iter_mut().find(|f| f.name == field.name) {
+            Some(f) => *f = field,
+            None => self.fields.push(field),
+        }
+    }
+
+    fn check(&self, table: &Table, path: &Path, fill_defaults: bool) -> Result<()> {
+        for field in &self.fields {
+            let path = path.field(&field.name);
+            let mut value = table.get::<Value>(field.name.as_str())?;
+            if value.is_nil() {
+                match &field.default {
+                    Some(default) if fill_defaults => {
+                        value = default(&table.0.lua.upgrade())?;
+                        table.set(field.name.as_str(), &value)?;
+                    }
+                    _ if field.required => return Err(path.error("missing required field")),
+                    _ => continue,
+                }
+            }
+            field.ty.check(&value, &path, fill_defaults)?;
+        }
+
+        if self.deny_unknown_fields {
+            for pair in table.pairs::<Value, Value>() {
+                let (key, _) = pair?;
+                let known = match &key {
+                    Value::String(s) => self.fields.iter().any(|f| *s == f.name),
+                    _ => false,
+                };
+                if !known {
+                    return Err(path.key(&key).error("unknown field"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for TableSchema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut map = f.debug_map();
+        for field in &self.fields {
+            map.entry(&field.name, &field.ty);
+        }
+        map.finish()
+    }
+}
+
+/// A type that is converted from a Lua table validated against a [`TableSchema`].
+///
+/// Implement this trait for types with a [`FromLua`] implementation and wrap them in [`Typed`]
+/// to validate (and fill in defaults) before the conversion.
+pub trait TypedTable: FromLua {
+    /// Returns the schema the table must match.
+    fn schema() -> TableSchema;
+}
+
+/// A wrapper that validates a Lua table against [`TypedTable::schema`] before converting it
+/// into `T`.
+///
+/// Missing fields with default values are written to the table before `T::from_lua` is called.
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{FieldType, FromLua, Lua, Result, TableSchema, Typed, TypedTable, Value};
+/// # fn main() -> Result<()> {
+/// struct Config {
+///     name: String,
+///     retries: u32,
+/// }
+///
+/// impl FromLua for Config {
+///     fn from_lua(value: Value, _: &Lua) -> Result<Self> {
+///         let table = value.as_table().unwrap();
+///         Ok(Config {
+///             name: table.get("name")?,
+///             retries: table.get("retries")?,
+///         })
+///     }
+/// }
+///
+/// impl TypedTable for Config {
+///     fn schema() -> TableSchema {
+///         TableSchema::new()
+///             .field("name", FieldType::String)
+///             .field_with_default("retries", FieldType::Integer, 3)
+///     }
+/// }
+///
+/// let lua = Lua::new();
+/// let Typed(config) = lua.load(r#"{name = "job"}"#).eval::<Typed<Config>>()?;
+/// assert_eq!(config.retries, 3);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Typed<T>(pub T);
+
+impl<T> Typed<T> {
+    /// Returns the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Typed<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Typed<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: TypedTable> FromLua for Typed<T> {
+    fn from_lua(value: Value, lua: &Lua) -> Result<Self> {
+        match value {
+            Value::Table(table) => {
+                T::schema().apply(&table)?;
+                T::from_lua(Value::Table(table), lua).map(Typed)
+            }
+            _ => Err(Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: T::type_name(),
+                message: Some("expected table".to_string()),
+            }),
+        }
+    }
+}
+
+// Path to a value inside nested tables, used in error messages.
+struct Path(StdString);
+
+impl Path {
+    fn root() -> Self {
+        Path(StdString::new())
+    }
+
+    fn field(&self, name: &str) -> Self {
+        self.join(PathSegment::Field(name))
+    }
+
+    fn index(&self, idx: usize) -> Self {
+        self.join(PathSegment::Index(idx))
+    }
+
+    fn key(&self, key: &Value) -> Self {
+        self.join(PathSegment::Key(key))
+    }
+
+    fn join(&self, segment: PathSegment) -> Self {
+        Path(join_path(&self.0, &segment.to_string()))
+    }
+
+    fn error(&self, message: impl Into<StdString>) -> Error {
+        Error::SchemaError {
+            path: self.0.clone(),
+            message: message.into(),
+        }
+    }
+}
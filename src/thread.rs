@@ -1,18 +1,20 @@
-use std::fmt;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
 use std::os::raw::{c_int, c_void};
+use std::string::String as StdString;
+use std::{fmt, mem, ptr};
 
 use crate::error::{Error, Result};
 use crate::function::Function;
+use crate::hook::Debug;
+use crate::interrupt::InterruptHandle;
 use crate::state::RawLua;
 use crate::traits::{FromLuaMulti, IntoLuaMulti};
-use crate::types::{LuaType, ValueRef};
-use crate::util::{check_stack, error_traceback_thread, pop_error, StackGuard};
+use crate::types::{AppData, AppDataRef, AppDataRefMut, LuaType, MaybeSend, ValueRef};
+use crate::util::{check_stack, error_traceback_thread, pop_error, to_string, StackGuard};
 
 #[cfg(not(feature = "luau"))]
-use crate::{
-    hook::{Debug, HookTriggers},
-    types::HookKind,
-};
+use crate::{hook::HookTriggers, types::HookKind};
 
 /// Continuation thread status. Can either be Ok, Yielded (rare, but can happen) or Error
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -148,23 +150,63 @@ impl Thread {
         R: FromLuaMulti,
     {
         let lua = self.0.lua.lock();
-        let mut pushed_nargs = match self.status_inner(&lua) {
+        unsafe {
+            self.resume_with(&lua, args, |_, nresults, thread_state| {
+                R::from_specified_stack_multi(nresults, &lua, thread_state)
+            })
+        }
+    }
+
+    // Resumes the thread and returns the yielded values, or `None` if the thread has finished
+    // (returned values are discarded)
+    fn resume_yielded<R>(&self, args: impl IntoLuaMulti) -> Result<Option<R>>
+    where
+        R: FromLuaMulti,
+    {
+        let lua = self.0.lua.lock();
+        if let ThreadStatusInner::Finished = self.status_inner(&lua) {
+            return Ok(None);
+        }
+        unsafe {
+            self.resume_with(&lua, args, |status, nresults, thread_state| match status {
+                ThreadStatusInner::Finished => Ok(None),
+                _ => R::from_specified_stack_multi(nresults, &lua, thread_state).map(Some),
+            })
+        }
+    }
+
+    // Pushes `args` onto the (resumable) thread stack, resumes it and passes the resulting status
+    // and number of results to `f`
+    unsafe fn resume_with<T>(
+        &self,
+        lua: &RawLua,
+        args: impl IntoLuaMulti,
+        f: impl FnOnce(ThreadStatusInner, c_int, *mut ffi::lua_State) -> Result<T>,
+    ) -> Result<T> {
+        let mut pushed_nargs = match self.status_inner(lua) {
             ThreadStatusInner::New(nargs) | ThreadStatusInner::Yielded(nargs) => nargs,
             _ => return Err(Error::CoroutineUnresumable),
         };
 
-        let state = lua.state();
         let thread_state = self.state();
-        unsafe {
-            let _sg = StackGuard::new(state);
-            let _thread_sg = StackGuard::with_top(thread_state, 0);
+        let _sg = StackGuard::new(lua.state());
+        let _thread_sg = StackGuard::with_top(thread_state, 0);
 
-            let nargs = args.push_into_specified_stack_multi(&lua, thread_state)?;
-            pushed_nargs += nargs;
+        let nargs = args.push_into_specified_stack_multi(lua, thread_state)?;
+        pushed_nargs += nargs;
 
-            let (_, nresults) = self.resume_inner(&lua, pushed_nargs)?;
+        let (status, nresults) = self.resume_inner(lua, pushed_nargs)?;
+        f(status, nresults, thread_state)
+    }
 
-            R::from_specified_stack_multi(nresults, &lua, thread_state)
+    /// Converts this thread into a [`Generator`] that iterates over values yielded by the thread.
+    ///
+    /// See [`Generator`] for details.
+    pub fn into_generator<T: FromLuaMulti>(self) -> Generator<T> {
+        Generator {
+            thread: self,
+            finished: false,
+            _phantom: PhantomData,
         }
     }
 
@@ -260,6 +302,20 @@ impl Thread {
         }
     }
 
+    // Returns `true` if the thread has been created but not started yet.
+    #[cfg(feature = "luau-lute")]
+    pub(crate) fn is_new(&self) -> bool {
+        let lua = self.0.lua.lock();
+        matches!(self.status_inner(&lua), ThreadStatusInner::New(_))
+    }
+
+    // Returns `true` if the thread is suspended by a yield.
+    #[cfg(feature = "luau-lute")]
+    pub(crate) fn is_yielded(&self) -> bool {
+        let lua = self.0.lua.lock();
+        matches!(self.status_inner(&lua), ThreadStatusInner::Yielded(_))
+    }
+
     /// Sets a hook function that will periodically be called as Lua code executes.
     ///
     /// This function is similar or [`Lua::set_hook`] except that it sets for the thread.
@@ -287,10 +343,8 @@ impl Thread {
     #[cfg(not(feature = "luau"))]
     #[cfg_attr(docsrs, doc(cfg(not(feature = "luau"))))]
     pub fn remove_hook(&self) {
-        let _lua = self.0.lua.lock();
-        unsafe {
-            ffi::lua_sethook(self.state(), None, 0, 0);
-        }
+        let lua = self.0.lua.lock();
+        unsafe { lua.remove_thread_hook(self.state()) }
     }
 
     /// Resets a thread
@@ -313,6 +367,7 @@ impl Thread {
         unsafe {
             let status = self.status_inner(&lua);
             self.reset_inner(status)?;
+            lua.detach_interrupt_handles(thread_state);
 
             // Push function to the top of the thread stack
             ffi::lua_xpush(lua.ref_thread(func.0.aux_thread), thread_state, func.0.index);
@@ -446,6 +501,176 @@ impl Thread {
         }
     }
 
+    /// Gets information about the thread call stack.
+    ///
+    /// Unlike [`Lua::inspect_stack`], this function inspects the stack of this thread, which can
+    /// be used to find where a suspended coroutine is parked or where an errored one failed.
+    /// Level `0` is the function at the top of the thread stack (for suspended threads it is
+    /// usually the function that called `coroutine.yield`).
+    ///
+    /// Returns `None` if the level is out of range (for example, if the thread was not started
+    /// or has finished).
+    ///
+    /// [`Lua::inspect_stack`]: crate::Lua::inspect_stack
+    pub fn inspect_stack(&self, level: usize) -> Option<Debug<'_>> {
+        let lua = self.0.lua.lock();
+        let thread_state = self.state();
+        unsafe {
+            let mut ar: ffi::lua_Debug = mem::zeroed();
+            let level = level as c_int;
+            #[cfg(not(feature = "luau"))]
+            if ffi::lua_getstack(thread_state, level, &mut ar) == 0 {
+                return None;
+            }
+            #[cfg(feature = "luau")]
+            if ffi::lua_getinfo(thread_state, level, cstr!(""), &mut ar) == 0 {
+                return None;
+            }
+            Some(Debug::new_thread(lua, thread_state, level, ar))
+        }
+    }
+
+    /// Returns a traceback of the thread call stack.
+    ///
+    /// The traceback has the same format as the one produced by `debug.traceback(thread)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result, Thread};
+    /// # fn main() -> Result<()> {
+    /// # let lua = Lua::new();
+    /// let thread: Thread = lua.load(r#"
+    ///     coroutine.create(function()
+    ///         local function park()
+    ///             coroutine.yield()
+    ///         end
+    ///         park()
+    ///     end)
+    /// "#).eval()?;
+    /// thread.resume::<()>(())?;
+    ///
+    /// let traceback = thread.traceback()?;
+    /// assert!(traceback.contains("park"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn traceback(&self) -> Result<StdString> {
+        let lua = self.0.lua.lock();
+        let state = lua.state();
+        let thread_state = self.state();
+        unsafe {
+            let _sg = StackGuard::new(state);
+            check_stack(state, ffi::LUA_TRACEBACK_STACK + 1)?;
+            protect_lua!(state, 0, 1, |state| {
+                ffi::luaL_traceback(state, thread_state, ptr::null(), 0)
+            })?;
+            Ok(to_string(state, -1))
+        }
+    }
+
+    /// Sets or replaces an application data object of type `T` associated with this thread.
+    ///
+    /// Unlike [`Lua::set_app_data`], the data is scoped to the thread (coroutine) and is dropped
+    /// automatically when the thread is garbage collected. Rust callbacks can access the data of
+    /// the thread they are called from using [`Lua::current_thread_data`].
+    ///
+    /// The data must not hold a strong reference to the thread itself, otherwise the thread (and
+    /// the data) would never be collected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread app data container is currently borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlua::{Lua, Result};
+    /// # fn main() -> Result<()> {
+    /// struct RequestId(u64);
+    ///
+    /// let lua = Lua::new();
+    /// let request_id = lua.create_function(|lua, ()| {
+    ///     Ok(lua.current_thread_data::<RequestId>().map(|id| id.0))
+    /// })?;
+    ///
+    /// let thread = lua.create_thread(request_id)?;
+    /// thread.set_app_data(RequestId(42))?;
+    /// assert_eq!(thread.resume::<Option<u64>>(())?, Some(42));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Lua::set_app_data`]: crate::Lua::set_app_data
+    /// [`Lua::current_thread_data`]: crate::Lua::current_thread_data
+    #[track_caller]
+    pub fn set_app_data<T: MaybeSend + 'static>(&self, data: T) -> Result<Option<T>> {
+        let lua = self.0.lua.lock();
+        let app_data = unsafe { lua.thread_app_data(self.state(), true)? };
+        Ok(app_data.and_then(|app_data| app_data.insert(data)))
+    }
+
+    /// Gets a reference to an application data object of type `T` associated with this thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data object of type `T` is currently mutably borrowed.
+    #[track_caller]
+    pub fn app_data_ref<T: 'static>(&self) -> Option<AppDataRef<'_, T>> {
+        let guard = self.0.lua.lock();
+        let app_data = unsafe { guard.thread_app_data(self.state(), false).ok()?? as *const AppData };
+        unsafe { &*app_data }.borrow(Some(guard))
+    }
+
+    /// Gets a mutable reference to an application data object of type `T` associated with this
+    /// thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data object of type `T` is currently borrowed.
+    #[track_caller]
+    pub fn app_data_mut<T: 'static>(&self) -> Option<AppDataRefMut<'_, T>> {
+        let guard = self.0.lua.lock();
+        let app_data = unsafe { guard.thread_app_data(self.state(), false).ok()?? as *const AppData };
+        unsafe { &*app_data }.borrow_mut(Some(guard))
+    }
+
+    /// Removes an application data object of type `T` associated with this thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread app data container is currently borrowed.
+    #[track_caller]
+    pub fn remove_app_data<T: 'static>(&self) -> Option<T> {
+        let lua = self.0.lua.lock();
+        let app_data = unsafe { lua.thread_app_data(self.state(), false).ok()?? };
+        app_data.remove()
+    }
+
+    // Removes all application data associated with this thread.
+    // Returns `false` if the data is currently borrowed.
+    pub(crate) fn clear_app_data(&self) -> bool {
+        let lua = self.0.lua.lock();
+        match unsafe { lua.thread_app_data(self.state(), false) } {
+            Ok(Some(app_data)) => app_data.try_clear(),
+            Ok(None) => true,
+            Err(_) => false,
+        }
+    }
+
+    /// Returns a handle that can be used to interrupt this thread from any (OS) thread.
+    ///
+    /// Unlike [`Lua::interrupt_handle`], the handle interrupts only code running in this thread
+    /// (coroutine). The handle keeps the thread alive and is detached when the thread is reset.
+    /// See [`InterruptHandle`] for details.
+    ///
+    /// [`Lua::interrupt_handle`]: crate::Lua::interrupt_handle
+    pub fn interrupt_handle(&self) -> Result<InterruptHandle> {
+        let lua = self.0.lua.lock();
+        let pin = lua.lua().create_registry_value(self)?;
+        Ok(unsafe { lua.interrupt_handle(self.state(), Some(pin)) })
+    }
+
     /// Converts this thread to a generic C pointer.
     ///
     /// There is no way to convert the pointer back to its original value.
@@ -469,10 +694,106 @@ impl PartialEq for Thread {
     }
 }
 
+/// An iterator over values yielded by a Lua thread (coroutine).
+///
+/// Each call to [`Iterator::next`] resumes the thread and returns the yielded values converted to
+/// `T`. The iteration stops when the thread finishes (values returned from the thread main
+/// function are not produced). Errors raised by the thread are returned as items, after which the
+/// iteration stops.
+///
+/// Values can be passed back into the thread (as results of `coroutine.yield`) using
+/// [`Generator::send`].
+///
+/// Created by [`Thread::into_generator`] or [`Function::as_generator`].
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{Function, Lua, Result};
+/// # fn main() -> Result<()> {
+/// let lua = Lua::new();
+/// let range: Function = lua.load(r#"
+///     function(n)
+///         for i = 1, n do
+///             coroutine.yield(i)
+///         end
+///     end
+/// "#).eval()?;
+///
+/// let mut generator = range.as_generator::<i64>()?;
+/// assert_eq!(generator.send(3).transpose()?, Some(1));
+/// assert_eq!(generator.collect::<Result<Vec<_>>>()?, vec![2, 3]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Function::as_generator`]: crate::Function::as_generator
+pub struct Generator<T> {
+    thread: Thread,
+    finished: bool,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: FromLuaMulti> Generator<T> {
+    /// Resumes the thread passing `args` as results of `coroutine.yield` (or as arguments of the
+    /// thread main function if it was not yet started), and returns the next yielded values.
+    ///
+    /// Returns `None` if the thread has finished.
+    pub fn send(&mut self, args: impl IntoLuaMulti) -> Option<Result<T>> {
+        if self.finished {
+            return None;
+        }
+        match self.thread.resume_yielded(args) {
+            Ok(Some(value)) => Some(Ok(value)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(err) => {
+                self.finished = true;
+                Some(Err(err))
+            }
+        }
+    }
+
+    /// Returns a reference to the underlying thread.
+    pub fn thread(&self) -> &Thread {
+        &self.thread
+    }
+
+    /// Consumes the generator, returning the underlying thread.
+    pub fn into_thread(self) -> Thread {
+        self.thread
+    }
+}
+
+impl<T: FromLuaMulti> Iterator for Generator<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.send(())
+    }
+}
+
+impl<T: FromLuaMulti> FusedIterator for Generator<T> {}
+
+impl<T> fmt::Debug for Generator<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Generator")
+            .field("thread", &self.thread)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
 impl LuaType for Thread {
     const TYPE_ID: c_int = ffi::LUA_TTHREAD;
 }
 
+mod pool;
+
+pub use pool::{PooledThread, ThreadPool, ThreadPoolStats};
+
 #[cfg(test)]
 mod assertions {
     use super::*;
@@ -481,4 +802,6 @@ mod assertions {
     static_assertions::assert_not_impl_any!(Thread: Send);
     #[cfg(feature = "send")]
     static_assertions::assert_impl_all!(Thread: Send, Sync);
+    #[cfg(feature = "send")]
+    static_assertions::assert_impl_all!(Generator<crate::Value>: Send, Sync);
 }
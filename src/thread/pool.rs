@@ -0,0 +1,235 @@
+use std::fmt;
+use std::ops::Deref;
+
+use parking_lot::Mutex;
+
+use crate::error::Result;
+use crate::function::Function;
+use crate::thread::Thread;
+use crate::types::XRc;
+
+/// A pool of reusable Lua threads (coroutines) bound to a function.
+///
+/// Creating a thread per task (for example per incoming request) and letting the garbage collector
+/// collect it afterwards can be expensive. The pool hands out threads with the function ready to
+/// run, and takes them back once the [`PooledThread`] guard is dropped. Returned threads are reset
+/// using [`Thread::reset`] (and their application data is cleared) before they can be reused.
+/// Resetting also detaches interrupt handles created by [`Thread::interrupt_handle`], so they
+/// cannot interrupt the next borrower of the thread.
+///
+/// Threads that cannot be reset are discarded: on Lua 5.4 and Luau any non-running thread
+/// (including one stopped by an error) can be reset, other Lua versions can reset only new or
+/// finished threads.
+///
+/// The pool keeps at most `max_size` idle threads, any extra threads are discarded.
+///
+/// Created by [`Lua::create_thread_pool`].
+///
+/// # Examples
+///
+/// ```
+/// # use mlua::{Lua, Result};
+/// # fn main() -> Result<()> {
+/// let lua = Lua::new();
+/// let handler = lua.create_function(|_, n: i64| Ok(n * 2))?;
+/// let pool = lua.create_thread_pool(handler, 16);
+///
+/// for n in 0..10 {
+///     let thread = pool.acquire()?;
+///     assert_eq!(thread.resume::<i64>(n)?, n * 2);
+/// }
+///
+/// let stats = pool.stats();
+/// assert_eq!(stats.created, 1);
+/// assert_eq!(stats.reused, 9);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Lua::create_thread_pool`]: crate::Lua::create_thread_pool
+#[derive(Clone)]
+pub struct ThreadPool(XRc<ThreadPoolInner>);
+
+struct ThreadPoolInner {
+    func: Function,
+    max_size: usize,
+    state: Mutex<PoolState>,
+}
+
+struct PoolState {
+    idle: Vec<Thread>,
+    stats: ThreadPoolStats,
+}
+
+/// Statistics of a [`ThreadPool`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ThreadPoolStats {
+    /// Number of threads created by the pool.
+    pub created: usize,
+    /// Number of times an idle thread was handed out instead of creating a new one.
+    pub reused: usize,
+    /// Number of threads returned to the pool after use.
+    pub recycled: usize,
+    /// Number of threads discarded (the pool was full or the thread could not be reset).
+    pub discarded: usize,
+    /// Number of idle threads currently in the pool.
+    pub idle: usize,
+}
+
+impl ThreadPool {
+    pub(crate) fn new(func: Function, max_size: usize) -> Self {
+        ThreadPool(XRc::new(ThreadPoolInner {
+            func,
+            max_size,
+            state: Mutex::new(PoolState {
+                idle: Vec::new(),
+                stats: ThreadPoolStats::default(),
+            }),
+        }))
+    }
+
+    /// Takes a thread from the pool, or creates a new one if the pool is empty.
+    ///
+    /// The thread is returned to the pool when the guard is dropped.
+    pub fn acquire(&self) -> Result<PooledThread> {
+        let thread = {
+            let mut state = self.0.state.lock();
+            let thread = state.idle.pop();
+            if thread.is_some() {
+                state.stats.reused += 1;
+            }
+            thread
+        };
+        let thread = match thread {
+            Some(thread) => thread,
+            None => {
+                let lua = self.0.func.0.lua.lock();
+                let thread = unsafe { lua.create_thread(&self.0.func)? };
+                self.0.state.lock().stats.created += 1;
+                thread
+            }
+        };
+        Ok(PooledThread {
+            thread: Some(thread),
+            pool: self.clone(),
+        })
+    }
+
+    /// Returns the function the pool threads are bound to.
+    pub fn function(&self) -> &Function {
+        &self.0.func
+    }
+
+    /// Returns the maximum number of idle threads kept in the pool.
+    pub fn max_size(&self) -> usize {
+        self.0.max_size
+    }
+
+    /// Returns the pool statistics.
+    pub fn stats(&self) -> ThreadPoolStats {
+        let state = self.0.state.lock();
+        ThreadPoolStats {
+            idle: state.idle.len(),
+            ..state.stats
+        }
+    }
+
+    /// Drops all idle threads.
+    pub fn clear(&self) {
+        let idle = std::mem::take(&mut self.0.state.lock().idle);
+        drop(idle);
+    }
+
+    fn recycle(&self, thread: Thread) {
+        {
+            let mut state = self.0.state.lock();
+            if state.idle.len() >= self.0.max_size {
+                state.stats.discarded += 1;
+                return;
+            }
+        }
+        // The Lua instance can be destroyed before the guard is dropped
+        let Some(_lua) = self.0.func.0.lua.try_lock() else {
+            self.0.state.lock().stats.discarded += 1;
+            return;
+        };
+        // Reset without holding the lock, as closing pending variables can run Lua code
+        let is_reset = match thread.reset(self.0.func.clone()) {
+            Ok(()) => true,
+            // Lua 5.4 reports the error that stopped the thread (or raised by closing methods), but
+            // the thread is reset anyway and can be bound to the function again
+            #[cfg(feature = "lua54")]
+            Err(_) => thread.reset(self.0.func.clone()).is_ok(),
+            #[cfg(not(feature = "lua54"))]
+            Err(_) => false,
+        };
+        let is_reset = is_reset && thread.clear_app_data();
+
+        let mut state = self.0.state.lock();
+        if is_reset && state.idle.len() < self.0.max_size {
+            state.idle.push(thread);
+            state.stats.recycled += 1;
+        } else {
+            state.stats.discarded += 1;
+        }
+    }
+}
+
+impl fmt::Debug for ThreadPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ThreadPool")
+            .field("max_size", &self.0.max_size)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+/// A thread taken from a [`ThreadPool`].
+///
+/// The thread is returned to the pool when the guard is dropped.
+pub struct PooledThread {
+    thread: Option<Thread>,
+    pool: ThreadPool,
+}
+
+impl PooledThread {
+    /// Detaches the thread from the pool, so it will not be returned to it.
+    pub fn detach(mut self) -> Thread {
+        self.thread.take().expect("thread is already taken")
+    }
+}
+
+impl Deref for PooledThread {
+    type Target = Thread;
+
+    fn deref(&self) -> &Thread {
+        self.thread.as_ref().expect("thread is already taken")
+    }
+}
+
+impl Drop for PooledThread {
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.pool.recycle(thread);
+        }
+    }
+}
+
+impl fmt::Debug for PooledThread {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("PooledThread").field(&self.thread).finish()
+    }
+}
+
+#[cfg(test)]
+mod assertions {
+    use super::*;
+
+    #[cfg(feature = "send")]
+    static_assertions::assert_impl_all!(ThreadPool: Send, Sync);
+    #[cfg(feature = "send")]
+    static_assertions::assert_impl_all!(PooledThread: Send, Sync);
+    #[cfg(not(feature = "send"))]
+    static_assertions::assert_not_impl_any!(ThreadPool: Send);
+}
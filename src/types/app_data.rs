@@ -103,6 +103,18 @@ impl AppData {
         }
     }
 
+    /// Removes all data objects, returning `false` if the container is currently borrowed.
+    pub(crate) fn try_clear(&self) -> bool {
+        if self.borrow.get() != 0 {
+            return false;
+        }
+        // SAFETY: we checked that there are no other references to the container
+        // Data is dropped outside of the container to allow destructors to access it.
+        let container = std::mem::take(unsafe { &mut *self.container.get() });
+        drop(container);
+        true
+    }
+
     #[track_caller]
     pub(crate) fn remove<T: 'static>(&self) -> Option<T> {
         if self.borrow.get() != 0 {
@@ -9,6 +9,7 @@ pub(crate) use error::{
     error_traceback, error_traceback_thread, init_error_registry, pop_error, protect_lua_call,
     protect_lua_closure, WrappedFailure,
 };
+pub(crate) use path::{join_path, PathSegment};
 pub(crate) use short_names::short_type_name;
 pub(crate) use types::TypeKey;
 pub(crate) use userdata::{
@@ -20,6 +21,9 @@ pub(crate) use userdata::{
 #[cfg(not(feature = "luau"))]
 pub(crate) use userdata::push_uninit_userdata;
 
+#[cfg(feature = "luau")]
+pub(crate) use userdata::{push_tagged_userdata, FIRST_USERDATA_TAG};
+
 // Checks that Lua has enough free stack space for future stack operations. On failure, this will
 // panic with an internal error message.
 #[inline]
@@ -329,6 +333,7 @@ pub(crate) fn linenumber_to_usize(n: c_int) -> Option<usize> {
 }
 
 mod error;
+mod path;
 mod short_names;
 mod types;
 mod userdata;
@@ -0,0 +1,48 @@
+use std::fmt;
+
+use crate::value::Value;
+
+// A segment of the path to a nested value, used in error messages.
+pub(crate) enum PathSegment<'a> {
+    // Sequence index (1-based, as in Lua)
+    Index(usize),
+    // Table key
+    Key(&'a Value),
+    // Table field, written as `name` if it's an identifier
+    Field(&'a str),
+    // Enum variant, written as is
+    Variant(&'a str),
+}
+
+impl fmt::Display for PathSegment<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathSegment::Index(i) => write!(f, "[{i}]"),
+            PathSegment::Key(Value::String(s)) => PathSegment::Field(&s.to_string_lossy()).fmt(f),
+            PathSegment::Key(Value::Integer(i)) => write!(f, "[{i}]"),
+            PathSegment::Key(Value::Number(n)) => write!(f, "[{n}]"),
+            PathSegment::Key(Value::Boolean(b)) => write!(f, "[{b}]"),
+            PathSegment::Key(key) => write!(f, "[{}]", key.type_name()),
+            PathSegment::Field(name) if is_identifier(name) => write!(f, "{name}"),
+            PathSegment::Field(name) => write!(f, "[{name:?}]"),
+            PathSegment::Variant(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+// Joins two parts of a path, separating them with a dot unless the second part is a subscript
+pub(crate) fn join_path(prefix: &str, suffix: &str) -> String {
+    let mut path = String::with_capacity(prefix.len() + suffix.len() + 1);
+    path.push_str(prefix);
+    if !(prefix.is_empty() || suffix.is_empty() || suffix.starts_with('[')) {
+        path.push('.');
+    }
+    path.push_str(suffix);
+    path
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
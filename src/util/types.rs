@@ -3,6 +3,9 @@ use std::os::raw::c_void;
 
 use crate::types::{Callback, CallbackUpvalue};
 
+#[cfg(not(feature = "luau"))]
+use crate::types::AppData;
+
 #[cfg(all(not(feature = "lua51"), not(feature = "luajit")))]
 use crate::types::ContinuationUpvalue;
 
@@ -72,3 +75,12 @@ impl TypeKey for crate::types::HookCallback {
         &HOOK_CALLBACK_TYPE_KEY as *const u8 as *const c_void
     }
 }
+
+#[cfg(not(feature = "luau"))]
+impl TypeKey for AppData {
+    #[inline(always)]
+    fn type_key() -> *const c_void {
+        static APP_DATA_TYPE_KEY: u8 = 0;
+        &APP_DATA_TYPE_KEY as *const u8 as *const c_void
+    }
+}
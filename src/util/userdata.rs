@@ -133,6 +133,30 @@ pub(crate) unsafe fn push_userdata<T>(state: *mut ffi::lua_State, t: T, protect:
     Ok(ud_ptr)
 }
 
+// Pushes the userdata with the given Luau tag.
+//
+// The tag must have a destructor for `T` registered using `lua_setuserdatadtor`.
+// Internally uses 3 stack spaces, does not call checkstack.
+#[cfg(feature = "luau")]
+#[inline]
+pub(crate) unsafe fn push_tagged_userdata<T>(
+    state: *mut ffi::lua_State,
+    t: T,
+    tag: c_int,
+    protect: bool,
+) -> Result<*mut T> {
+    let size = const { mem::size_of::<T>() };
+
+    let ud_ptr = if protect {
+        protect_lua!(state, 0, 1, |state| ffi::lua_newuserdatatagged(state, size, tag))?
+    } else {
+        ffi::lua_newuserdatatagged(state, size, tag)
+    } as *mut T;
+
+    ptr::write(ud_ptr, t);
+    Ok(ud_ptr)
+}
+
 #[inline]
 #[track_caller]
 pub(crate) unsafe fn get_userdata<T>(state: *mut ffi::lua_State, index: c_int) -> *mut T {
@@ -160,7 +184,7 @@ pub(crate) unsafe fn take_userdata<T>(state: *mut ffi::lua_State, idx: c_int) ->
 
     // Update userdata tag to disable destructor and mark as destructed
     #[cfg(feature = "luau")]
-    ffi::lua_setuserdatatag(state, idx, 1);
+    ffi::lua_setuserdatatag(state, idx, DESTRUCTED_USERDATA_TAG);
 
     ptr::read(ud)
 }
@@ -171,3 +195,11 @@ pub(crate) unsafe fn get_destructed_userdata_metatable(state: *mut ffi::lua_Stat
 }
 
 pub(crate) static DESTRUCTED_USERDATA_METATABLE: u8 = 0;
+
+// Luau userdata tag of destructed userdata (no destructor is registered for it)
+#[cfg(feature = "luau")]
+pub(crate) const DESTRUCTED_USERDATA_TAG: c_int = 1;
+
+// First Luau userdata tag that can be assigned to a registered Rust type
+#[cfg(feature = "luau")]
+pub(crate) const FIRST_USERDATA_TAG: c_int = 2;
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
 #[cfg(feature = "serde")]
 use serde::ser::{Serialize, SerializeTupleStruct, Serializer};
@@ -7,7 +8,10 @@ use serde::ser::{Serialize, SerializeTupleStruct, Serializer};
 ///
 /// By default vectors are 3-dimensional, but can be 4-dimensional
 /// if the `luau-vector4` feature is enabled.
-#[cfg_attr(docsrs, doc(cfg(feature = "luau")))]
+///
+/// On other Lua versions vectors are available with the `userdata-vector` feature and represented
+/// as userdata with the same set of operations.
+#[cfg_attr(docsrs, doc(cfg(any(feature = "luau", feature = "userdata-vector"))))]
 #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
 pub struct Vector(pub(crate) [f32; Self::SIZE]);
 
@@ -63,6 +67,200 @@ impl Vector {
     pub const fn w(&self) -> f32 {
         self.0[3]
     }
+
+    /// Creates a new vector with all components set to `v`.
+    pub const fn splat(v: f32) -> Self {
+        Self([v; Self::SIZE])
+    }
+
+    /// Returns the dot product of `self` and `other`.
+    pub fn dot(self, other: Self) -> f32 {
+        (self.0.iter().zip(other.0.iter())).fold(0.0, |acc, (a, b)| acc + a * b)
+    }
+
+    /// Returns the cross product of `self` and `other`.
+    ///
+    /// Only the first 3 components are used, the 4th component (if any) of the result is `0.0`.
+    pub fn cross(self, other: Self) -> Self {
+        let mut v = Self::zero();
+        v.0[0] = self.y() * other.z() - self.z() * other.y();
+        v.0[1] = self.z() * other.x() - self.x() * other.z();
+        v.0[2] = self.x() * other.y() - self.y() * other.x();
+        v
+    }
+
+    /// Returns the length (magnitude) of the vector.
+    pub fn length(self) -> f32 {
+        self.length_squared().sqrt()
+    }
+
+    /// Returns the squared length of the vector.
+    pub fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    /// Returns the vector with the same direction as `self` and length `1.0`.
+    ///
+    /// The result is non-finite if the vector length is zero.
+    pub fn normalize(self) -> Self {
+        self / self.length()
+    }
+
+    /// Returns the vector components as an array.
+    pub const fn to_array(self) -> [f32; Self::SIZE] {
+        self.0
+    }
+
+    #[inline(always)]
+    fn map(self, f: impl Fn(f32) -> f32) -> Self {
+        Self(self.0.map(f))
+    }
+
+    #[inline(always)]
+    fn zip_map(self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        let mut v = self;
+        for (a, b) in v.0.iter_mut().zip(other.0) {
+            *a = f(*a, b);
+        }
+        v
+    }
+}
+
+macro_rules! impl_vector_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:tt) => {
+        impl $trait for Vector {
+            type Output = Vector;
+
+            #[inline]
+            fn $method(self, rhs: Vector) -> Vector {
+                self.zip_map(rhs, |a, b| a $op b)
+            }
+        }
+
+        impl $trait<f32> for Vector {
+            type Output = Vector;
+
+            #[inline]
+            fn $method(self, rhs: f32) -> Vector {
+                self.map(|a| a $op rhs)
+            }
+        }
+
+        impl $trait<Vector> for f32 {
+            type Output = Vector;
+
+            #[inline]
+            fn $method(self, rhs: Vector) -> Vector {
+                rhs.map(|b| self $op b)
+            }
+        }
+
+        impl $assign_trait for Vector {
+            #[inline]
+            fn $assign_method(&mut self, rhs: Vector) {
+                *self = *self $op rhs;
+            }
+        }
+
+        impl $assign_trait<f32> for Vector {
+            #[inline]
+            fn $assign_method(&mut self, rhs: f32) {
+                *self = *self $op rhs;
+            }
+        }
+    };
+}
+
+impl_vector_op!(Add, add, AddAssign, add_assign, +);
+impl_vector_op!(Sub, sub, SubAssign, sub_assign, -);
+impl_vector_op!(Mul, mul, MulAssign, mul_assign, *);
+impl_vector_op!(Div, div, DivAssign, div_assign, /);
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    #[inline]
+    fn neg(self) -> Vector {
+        self.map(|a| -a)
+    }
+}
+
+impl From<[f32; Self::SIZE]> for Vector {
+    #[inline]
+    fn from(v: [f32; Self::SIZE]) -> Self {
+        Self(v)
+    }
+}
+
+impl From<Vector> for [f32; Vector::SIZE] {
+    #[inline]
+    fn from(v: Vector) -> Self {
+        v.0
+    }
+}
+
+#[cfg(all(feature = "glam", not(feature = "luau-vector4")))]
+impl From<glam::Vec3> for Vector {
+    #[inline]
+    fn from(v: glam::Vec3) -> Self {
+        Self(v.to_array())
+    }
+}
+
+#[cfg(all(feature = "glam", not(feature = "luau-vector4")))]
+impl From<Vector> for glam::Vec3 {
+    #[inline]
+    fn from(v: Vector) -> Self {
+        glam::Vec3::from_array(v.0)
+    }
+}
+
+#[cfg(all(feature = "glam", feature = "luau-vector4"))]
+impl From<glam::Vec4> for Vector {
+    #[inline]
+    fn from(v: glam::Vec4) -> Self {
+        Self(v.to_array())
+    }
+}
+
+#[cfg(all(feature = "glam", feature = "luau-vector4"))]
+impl From<Vector> for glam::Vec4 {
+    #[inline]
+    fn from(v: Vector) -> Self {
+        glam::Vec4::from_array(v.0)
+    }
+}
+
+#[cfg(all(feature = "mint", not(feature = "luau-vector4")))]
+impl From<mint::Vector3<f32>> for Vector {
+    #[inline]
+    fn from(v: mint::Vector3<f32>) -> Self {
+        Self(v.into())
+    }
+}
+
+#[cfg(all(feature = "mint", not(feature = "luau-vector4")))]
+impl From<Vector> for mint::Vector3<f32> {
+    #[inline]
+    fn from(v: Vector) -> Self {
+        v.0.into()
+    }
+}
+
+#[cfg(all(feature = "mint", feature = "luau-vector4"))]
+impl From<mint::Vector4<f32>> for Vector {
+    #[inline]
+    fn from(v: mint::Vector4<f32>) -> Self {
+        Self(v.into())
+    }
+}
+
+#[cfg(all(feature = "mint", feature = "luau-vector4"))]
+impl From<Vector> for mint::Vector4<f32> {
+    #[inline]
+    fn from(v: Vector) -> Self {
+        v.0.into()
+    }
 }
 
 #[cfg(feature = "serde")]
@@ -89,3 +287,62 @@ impl PartialEq<[f32; Self::SIZE]> for Vector {
 impl crate::types::LuaType for Vector {
     const TYPE_ID: std::os::raw::c_int = ffi::LUA_TVECTOR;
 }
+
+#[cfg(all(feature = "userdata-vector", not(feature = "luau")))]
+mod userdata_impl {
+    use super::Vector;
+    use crate::error::{Error, Result};
+    use crate::types::Either;
+    use crate::userdata::{MetaMethod, UserData, UserDataFields, UserDataMethods};
+
+    type Operand = Either<Vector, f32>;
+
+    fn binary_op(
+        a: Operand,
+        b: Operand,
+        vv: fn(Vector, Vector) -> Vector,
+        vs: fn(Vector, f32) -> Vector,
+        sv: fn(f32, Vector) -> Vector,
+    ) -> Result<Vector> {
+        match (a, b) {
+            (Either::Left(a), Either::Left(b)) => Ok(vv(a, b)),
+            (Either::Left(a), Either::Right(b)) => Ok(vs(a, b)),
+            (Either::Right(a), Either::Left(b)) => Ok(sv(a, b)),
+            (Either::Right(_), Either::Right(_)) => {
+                Err(Error::runtime("expected at least one vector operand"))
+            }
+        }
+    }
+
+    impl UserData for Vector {
+        fn add_fields<F: UserDataFields<Self>>(fields: &mut F) {
+            fields.add_meta_field(MetaMethod::Type, "vector");
+            fields.add_field_method_get("x", |_, this| Ok(this.x()));
+            fields.add_field_method_get("y", |_, this| Ok(this.y()));
+            fields.add_field_method_get("z", |_, this| Ok(this.z()));
+        }
+
+        fn add_methods<M: UserDataMethods<Self>>(methods: &mut M) {
+            methods.add_method("dot", |_, this, other: Vector| Ok(this.dot(other)));
+            methods.add_method("cross", |_, this, other: Vector| Ok(this.cross(other)));
+            methods.add_method("magnitude", |_, this, ()| Ok(this.length()));
+            methods.add_method("normalize", |_, this, ()| Ok(this.normalize()));
+
+            methods.add_meta_function(MetaMethod::Add, |_, (a, b): (Operand, Operand)| {
+                binary_op(a, b, |a, b| a + b, |a, b| a + b, |a, b| a + b)
+            });
+            methods.add_meta_function(MetaMethod::Sub, |_, (a, b): (Operand, Operand)| {
+                binary_op(a, b, |a, b| a - b, |a, b| a - b, |a, b| a - b)
+            });
+            methods.add_meta_function(MetaMethod::Mul, |_, (a, b): (Operand, Operand)| {
+                binary_op(a, b, |a, b| a * b, |a, b| a * b, |a, b| a * b)
+            });
+            methods.add_meta_function(MetaMethod::Div, |_, (a, b): (Operand, Operand)| {
+                binary_op(a, b, |a, b| a / b, |a, b| a / b, |a, b| a / b)
+            });
+            methods.add_meta_method(MetaMethod::Unm, |_, this, ()| Ok(-*this));
+            methods.add_meta_method(MetaMethod::Eq, |_, this, other: Vector| Ok(*this == other));
+            methods.add_meta_method(MetaMethod::ToString, |_, this, ()| Ok(this.to_string()));
+        }
+    }
+}
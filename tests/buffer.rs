@@ -1,5 +1,7 @@
 #![cfg(feature = "luau")]
 
+use std::io::{Read, Seek, SeekFrom, Write};
+
 use mlua::{Lua, Result, Value};
 
 #[test]
@@ -40,6 +42,65 @@ fn test_buffer() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_buffer_typed_access() -> Result<()> {
+    let lua = Lua::new();
+
+    let buf = lua.create_buffer_with_capacity(16)?;
+    assert_eq!(buf.to_vec(), [0; 16]);
+
+    buf.write_u32_le(0, 0x01020304)?;
+    buf.write_u16_be(4, 0x0506)?;
+    buf.write_f64_le(8, 1.5)?;
+    assert_eq!(buf.read_bytes::<6>(0), [4, 3, 2, 1, 5, 6]);
+    assert_eq!(buf.read_u32_le(0)?, 0x01020304);
+    assert_eq!(buf.read_u32_be(0)?, 0x04030201);
+    assert_eq!(buf.read_i8(0)?, 4);
+    assert_eq!(buf.read_f64_le(8)?, 1.5);
+
+    // Check that Luau sees the same data
+    let f = lua.load("function(b) return buffer.readu32(b, 0), buffer.readf64(b, 8) end");
+    let (n, x): (u32, f64) = f.eval::<mlua::Function>()?.call(&buf)?;
+    assert_eq!((n, x), (0x01020304, 1.5));
+
+    // Out of bounds access returns an error
+    assert!(buf.read_u64_le(9).is_err());
+    assert!(buf.write_u8(16, 0).is_err());
+    assert!(buf.try_read_bytes::<1>(usize::MAX).is_err());
+    assert!(buf.try_write_bytes(15, b"!!").is_err());
+
+    // Scoped access
+    unsafe { buf.with_bytes_mut(|data| data[15] = 42) };
+    assert_eq!(unsafe { buf.with_bytes(|data| data[15]) }, 42);
+
+    Ok(())
+}
+
+#[test]
+fn test_buffer_cursor() -> Result<()> {
+    let lua = Lua::new();
+
+    let mut cursor = lua.create_buffer_with_capacity(8)?.cursor();
+    cursor.write_all(b"hello")?;
+    assert_eq!(cursor.position(), 5);
+    // Buffer has a fixed size, extra bytes are not written
+    assert_eq!(cursor.write(b"world")?, 3);
+    assert_eq!(cursor.write(b"!")?, 0);
+
+    cursor.seek(SeekFrom::Start(1))?;
+    let mut data = Vec::new();
+    cursor.read_to_end(&mut data)?;
+    assert_eq!(data, b"ellowor");
+
+    assert_eq!(cursor.seek(SeekFrom::End(-3))?, 5);
+    assert_eq!(cursor.seek(SeekFrom::Current(1))?, 6);
+    assert!(cursor.seek(SeekFrom::Current(-7)).is_err());
+
+    assert_eq!(cursor.into_inner().to_vec(), b"hellowor");
+
+    Ok(())
+}
+
 #[test]
 #[should_panic(expected = "range end index 14 out of range for slice of length 13")]
 fn test_buffer_out_of_bounds_read() {
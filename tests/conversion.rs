@@ -748,3 +748,167 @@ fn test_char_from_lua() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(feature = "std-types")]
+#[test]
+fn test_time_conversion() -> Result<()> {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    let lua = Lua::new();
+
+    let d = Duration::from_millis(1500);
+    assert_eq!(d.into_lua(&lua)?, Value::Number(1.5));
+    assert_eq!(lua.convert::<Duration>(2)?, Duration::from_secs(2));
+    assert!(lua
+        .convert::<Duration>(-1)
+        .is_err_and(|e| e.to_string().contains("error converting Lua integer to Duration")));
+
+    let t = UNIX_EPOCH + Duration::from_secs(1_000_000);
+    assert_eq!(lua.convert::<SystemTime>(t)?, t);
+    assert_eq!(
+        lua.convert::<SystemTime>(-10)?,
+        UNIX_EPOCH - Duration::from_secs(10)
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "std-types")]
+#[test]
+fn test_net_conversion() -> Result<()> {
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+    let lua = Lua::new();
+
+    let ip: IpAddr = "::1".parse().unwrap();
+    assert_eq!(ip.into_lua(&lua)?.to_string()?, "::1");
+    assert_eq!(lua.convert::<Ipv4Addr>("127.0.0.1")?, Ipv4Addr::LOCALHOST);
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    assert_eq!(lua.convert::<SocketAddr>(addr)?, addr);
+    assert!(lua
+        .convert::<IpAddr>("localhost")
+        .is_err_and(|e| e.to_string().contains("error converting Lua string to IpAddr")));
+    assert!(lua
+        .convert::<IpAddr>(123)
+        .is_err_and(|e| e.to_string().contains("expected string")));
+
+    Ok(())
+}
+
+#[cfg(feature = "std-types")]
+#[test]
+fn test_vecdeque_conversion() -> Result<()> {
+    use std::collections::VecDeque;
+
+    let lua = Lua::new();
+
+    let v = VecDeque::from([1, 2, 3]);
+    assert_eq!(lua.convert::<Vec<i32>>(v.clone())?, vec![1, 2, 3]);
+    assert_eq!(lua.convert::<VecDeque<i32>>(vec![1, 2, 3])?, v);
+
+    Ok(())
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono_conversion() -> Result<()> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+
+    let lua = Lua::new();
+
+    let dt = DateTime::parse_from_rfc3339("2024-01-02T03:04:05.5+01:00").unwrap();
+    assert_eq!(dt.into_lua(&lua)?.to_string()?, "2024-01-02T03:04:05.500+01:00");
+    assert_eq!(lua.convert::<DateTime<Utc>>(dt)?, dt.to_utc());
+    assert_eq!(
+        lua.convert::<DateTime<Utc>>(0)?,
+        DateTime::from_timestamp(0, 0).unwrap()
+    );
+    assert!(lua
+        .convert::<DateTime<Utc>>(f64::NAN)
+        .is_err_and(|e| e.to_string().contains("expected finite number")));
+
+    let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+    assert_eq!(lua.convert::<NaiveDate>(date)?, date);
+    let ndt = date.and_hms_opt(12, 30, 0).unwrap();
+    assert_eq!(lua.convert::<NaiveDateTime>(ndt)?, ndt);
+
+    assert_eq!(lua.convert::<TimeDelta>(1.5)?, TimeDelta::milliseconds(1500));
+    assert!(lua
+        .convert::<NaiveDate>("not a date")
+        .is_err_and(|e| e.to_string().contains("error converting Lua string to NaiveDate")));
+
+    Ok(())
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn test_uuid_conversion() -> Result<()> {
+    let lua = Lua::new();
+
+    let id = uuid::Uuid::from_u128(0x1234);
+    assert_eq!(
+        id.into_lua(&lua)?.to_string()?,
+        "00000000-0000-0000-0000-000000001234"
+    );
+    assert_eq!(lua.convert::<uuid::Uuid>(id)?, id);
+    assert!(lua.convert::<uuid::Uuid>("1234").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_bytes_conversion() -> Result<()> {
+    let lua = Lua::new();
+
+    let b = bytes::Bytes::from_static(b"hello\xff");
+    assert_eq!(
+        lua.convert::<BString>(b.clone())?,
+        BString::from(&b"hello\xff"[..])
+    );
+    assert_eq!(
+        lua.convert::<bytes::Bytes>("hello")?,
+        bytes::Bytes::from_static(b"hello")
+    );
+    assert_eq!(lua.convert::<bytes::BytesMut>("hello")?, &b"hello"[..]);
+
+    Ok(())
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn test_indexmap_conversion() -> Result<()> {
+    let lua = Lua::new();
+
+    let map = indexmap::indexmap! {"a".to_string() => 1, "b".to_string() => 2};
+    let map2 = lua.convert::<indexmap::IndexMap<String, i32>>(map.clone())?;
+    assert_eq!(map2.len(), 2);
+    assert_eq!(map2["a"], 1);
+    let set = lua.convert::<indexmap::IndexSet<i32>>(vec![3, 1, 2])?;
+    assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![3, 1, 2]);
+
+    Ok(())
+}
+
+#[cfg(feature = "smallvec")]
+#[test]
+fn test_smallvec_conversion() -> Result<()> {
+    let lua = Lua::new();
+
+    let v: smallvec::SmallVec<[i32; 4]> = smallvec::smallvec![1, 2, 3];
+    assert_eq!(lua.convert::<smallvec::SmallVec<[i32; 4]>>(v.clone())?, v);
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_value_conversion() -> Result<()> {
+    let lua = Lua::new();
+
+    let json = serde_json::json!({"a": [1, 2, null], "b": {"c": "d"}});
+    let value = json.clone().into_lua(&lua)?;
+    assert_eq!(lua.convert::<serde_json::Value>(value)?, json);
+
+    Ok(())
+}
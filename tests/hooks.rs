@@ -325,3 +325,22 @@ fn test_global_hook() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_remove_global_hook_keeps_interrupts() -> Result<()> {
+    let lua = Lua::new();
+    let handle = lua.interrupt_handle();
+
+    lua.set_global_hook(HookTriggers::new().every_nth_instruction(10), |_, _| {
+        Ok(VmState::Continue)
+    })?;
+    lua.remove_global_hook();
+
+    // The stale global hook removes itself, but the interrupt hook must stay installed
+    lua.load("for i = 1, 1000 do end").exec()?;
+    handle.interrupt();
+    let result = lua.load("for i = 1, 1000000 do end").exec();
+    assert!(matches!(result, Err(Error::Interrupted)), "{result:?}");
+
+    Ok(())
+}
@@ -58,6 +58,39 @@ fn test_vectors() -> Result<()> {
     Ok(())
 }
 
+#[cfg(not(feature = "luau-vector4"))]
+#[test]
+fn test_vector_math() -> Result<()> {
+    let lua = Lua::new();
+
+    let a = Vector::new(1.0, 2.0, 3.0);
+    let b = Vector::new(3.0, 2.0, 1.0);
+    assert_eq!(a + b, [4.0, 4.0, 4.0]);
+    assert_eq!(a - b, [-2.0, 0.0, 2.0]);
+    assert_eq!(a * b, [3.0, 4.0, 3.0]);
+    assert_eq!(a * 2.0, [2.0, 4.0, 6.0]);
+    assert_eq!(2.0 * a, [2.0, 4.0, 6.0]);
+    assert_eq!(a / 2.0, [0.5, 1.0, 1.5]);
+    assert_eq!(-a, [-1.0, -2.0, -3.0]);
+    assert_eq!(a.dot(b), 10.0);
+    assert_eq!(a.cross(b), [-4.0, 8.0, -4.0]);
+    assert_eq!(Vector::new(3.0, 4.0, 0.0).length(), 5.0);
+    assert_eq!(Vector::new(3.0, 4.0, 0.0).normalize(), [0.6, 0.8, 0.0]);
+    assert_eq!(Vector::from([1.0, 2.0, 3.0]), a);
+    assert_eq!(<[f32; 3]>::from(a), [1.0, 2.0, 3.0]);
+
+    // Check that Rust and Luau agree
+    let f = lua
+        .load("function(a, b) return vector.cross(a, b), vector.dot(a, b), vector.magnitude(a) end")
+        .eval::<Function>()?;
+    let (cross, dot, len): (Vector, f32, f32) = f.call((a, b))?;
+    assert_eq!(cross, a.cross(b));
+    assert_eq!(dot, a.dot(b));
+    assert_eq!(len, a.length());
+
+    Ok(())
+}
+
 #[cfg(feature = "luau-vector4")]
 #[test]
 fn test_vectors() -> Result<()> {
@@ -135,3 +135,136 @@ fn test_lute_runtime() -> LuaResult<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_lute_run_until_complete() -> LuaResult<()> {
+    let lua = Lua::new();
+    let lute = lua.lute()?;
+    lute.load_stdlib(LuaLuteStdLib::TASK)?;
+    let task = lute.task()?.expect("Task library is not loaded");
+    lua.globals().set("task", task)?;
+
+    // Thread that completes without yielding
+    let thread = lua.create_thread(lua.load("return 1 + 2").into_function()?)?;
+    assert_eq!(lute.run_until_complete::<i32>(&thread)?, 3);
+
+    // Thread that waits on the scheduler
+    let thread = lua.create_thread(lua.load("task.wait(0.01); return 'done'").into_function()?)?;
+    assert_eq!(lute.run_until_complete::<String>(&thread)?, "done");
+    assert!(lute.poll()?.is_idle());
+
+    // Errors are propagated
+    let thread = lua.create_thread(lua.load("task.wait(0.01); error('boom')").into_function()?)?;
+    match lute.run_until_complete::<()>(&thread) {
+        Err(err) => assert!(err.to_string().contains("boom")),
+        Ok(_) => panic!("expected error"),
+    }
+
+    // Errors of other tasks do not abort the wait
+    let other = lua.create_thread(lua.load("task.wait(0.01); error('other')").into_function()?)?;
+    other.resume::<()>(())?;
+    let thread = lua.create_thread(lua.load("task.wait(0.05); return 'done'").into_function()?)?;
+    assert_eq!(lute.run_until_complete::<String>(&thread)?, "done");
+
+    // Their errors are collected
+    let errors = lute.take_task_errors()?;
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].to_string().contains("other"), "{}", errors[0]);
+    assert!(lute.take_task_errors()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_lute_vm_policy() -> LuaResult<()> {
+    let lua = Lua::new();
+    let root = tempfile::tempdir().unwrap();
+    std::fs::write(root.path().join("inside.txt"), "hello").unwrap();
+
+    // Processes cannot be scoped to a directory
+    let policy = LuaLuteVmPolicy::new(LuaLuteStdLib::FS | LuaLuteStdLib::PROCESS).fs_root(root.path());
+    assert!(lua.lute()?.apply_policy(&policy).is_err());
+
+    let policy = LuaLuteVmPolicy::new(LuaLuteStdLib::FS | LuaLuteStdLib::TASK).fs_root(root.path());
+    lua.lute()?.apply_policy(&policy)?;
+
+    lua.load(
+        r#"
+        assert(type(fs) == "table" and type(task) == "table")
+        assert(process == nil and time == nil)
+
+        -- Access inside of the root
+        assert(fs.exists("inside.txt"))
+        assert(fs.exists("./sub/../inside.txt"))
+        fs.mkdir("sub")
+
+        -- Access outside of the root
+        for _, name in {"exists", "mkdir", "remove", "listdir", "stat"} do
+            local ok, err = pcall(fs[name], "../outside")
+            assert(not ok and string.find(tostring(err), "is not allowed"), name)
+        end
+    "#,
+    )
+    .exec()?;
+    assert!(root.path().join("sub").is_dir());
+
+    // Dangling symlinks pointing outside of the root are rejected
+    #[cfg(unix)]
+    {
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path().join("missing"), root.path().join("link")).unwrap();
+        lua.load(
+            r#"
+            for _, path in {"link", "link/sub"} do
+                local ok, err = pcall(fs.mkdir, path)
+                assert(not ok and string.find(tostring(err), "is not allowed"), path)
+            end
+        "#,
+        )
+        .exec()?;
+        assert!(!outside.path().join("missing").exists());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_lute_child_vm_policy() -> LuaResult<()> {
+    use std::sync::{Arc, Mutex};
+
+    let lua = Lua::new();
+    let root = tempfile::tempdir().unwrap();
+    std::fs::write(root.path().join("child.luau"), "return {}").unwrap();
+
+    let lute = lua.lute()?;
+    lute.load_stdlib(LuaLuteStdLib::VM)?;
+    lua.globals()
+        .set("vm", lute.vm()?.expect("VM library is not loaded"))?;
+
+    // Child VMs only get `task` and `time`
+    lute.set_child_vm_policy(Some(LuaLuteVmPolicy::new(
+        LuaLuteStdLib::TASK | LuaLuteStdLib::TIME,
+    )))?;
+    let globals = Arc::new(Mutex::new(Vec::new()));
+    let child_globals = globals.clone();
+    lute.set_runtime_initter(move |_, child, vm_type| {
+        if vm_type == LuaLuteChildVmType::ChildVm {
+            for name in ["task", "time", "fs", "process"] {
+                let lib = child.globals().get::<Option<LuaTable>>(name)?;
+                child_globals.lock().unwrap().push((name, lib.is_some()));
+            }
+        }
+        Ok(())
+    })?;
+
+    let path = root.path().join("child");
+    let create = lua.load("vm.create(...)").into_function()?;
+    lua.create_thread(create)?.resume::<()>(path.to_str().unwrap())?;
+
+    assert_eq!(
+        *globals.lock().unwrap(),
+        vec![("task", true), ("time", true), ("fs", false), ("process", false)]
+    );
+
+    Ok(())
+}
@@ -83,3 +83,122 @@ fn test_userdata_multithread_access_sync() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_lua_bytes_move_to_thread() -> Result<()> {
+    let lua = Lua::new();
+
+    let s = lua.create_string("x".repeat(1 << 20))?;
+    let bytes = s.to_lua_bytes()?;
+    let ptr = bytes.as_ptr() as usize;
+
+    let handle = std::thread::spawn(move || {
+        assert_eq!(bytes.as_ptr() as usize, ptr);
+        bytes.iter().all(|&b| b == b'x')
+    });
+    assert!(handle.join().unwrap());
+
+    Ok(())
+}
+
+#[cfg(feature = "actor")]
+#[test]
+fn test_actors() -> Result<()> {
+    use mlua::{ActorValue, Actors};
+    use std::sync::Arc;
+
+    let actors = Actors::new(
+        3,
+        r#"
+        while true do
+            local msg, from = actor.receive()
+            if msg == nil then break end
+            if type(msg) == "table" then
+                local sum = 0
+                for _, n in ipairs(msg) do
+                    sum = sum + n
+                end
+                actor.send({ id = actor.id, sum = sum })
+            elseif msg == "forward" then
+                actor.post((actor.id + 1) % actor.count, "ping")
+            elseif msg == "ping" then
+                actor.send({ id = actor.id, from = from })
+            end
+        end
+    "#,
+    )?;
+    assert_eq!(actors.worker_count(), 3);
+
+    let numbers = ActorValue::Table(
+        (1..=4)
+            .map(|i| (ActorValue::Integer(i), ActorValue::Integer(i)))
+            .collect(),
+    );
+    actors.broadcast(numbers);
+    let mut results = (0..3)
+        .map(|_| {
+            let (id, msg) = actors.receive().unwrap();
+            assert_eq!(msg.get("id").and_then(|v| v.as_integer()), Some(id as _));
+            (id, msg.get("sum").and_then(|v| v.as_integer()))
+        })
+        .collect::<Vec<_>>();
+    results.sort();
+    assert_eq!(results, vec![(0, Some(10)), (1, Some(10)), (2, Some(10))]);
+
+    // Worker to worker messages
+    actors.send(2, "forward")?;
+    let (id, msg) = actors.receive().unwrap();
+    assert_eq!(id, 0);
+    assert_eq!(msg.get("from").and_then(|v| v.as_integer()), Some(2));
+
+    assert!(actors.send(3, "forward").is_err());
+    assert!(actors.try_receive().is_none());
+    actors.join()?;
+
+    // Errors are reported on join
+    let actors = Actors::new(2, "actor.send(function() end)")?;
+    match actors.join() {
+        Err(Error::CallbackError { cause, .. }) => match cause.as_ref() {
+            Error::BadArgument { cause, .. } => match cause.as_ref() {
+                Error::FromLuaConversionError { to, .. } => assert_eq!(to, "ActorValue"),
+                err => panic!("expected Error::FromLuaConversionError, got {err:?}"),
+            },
+            err => panic!("expected Error::BadArgument, got {err:?}"),
+        },
+        r => panic!("expected Error::CallbackError, got {r:?}"),
+    }
+
+    // Recursive tables cannot be sent
+    let lua = Lua::new();
+    let value = lua.load("local t = {}; t.t = t; return t").eval::<ActorValue>();
+    assert!(value.is_err());
+
+    // Tables with metatables cannot be sent
+    let value = lua.load("return setmetatable({}, {})").eval::<ActorValue>();
+    assert!(value.is_err());
+
+    // Dropping interrupts running scripts
+    let alive = Arc::new(());
+    let actors = Actors::with_factory(2, "while true do end", {
+        let alive = alive.clone();
+        move || {
+            let lua = Lua::new();
+            lua.set_app_data(alive.clone());
+            Ok(lua)
+        }
+    })?;
+    while Arc::strong_count(&alive) < 4 {
+        std::thread::yield_now();
+    }
+    drop(actors);
+    let start = std::time::Instant::now();
+    while Arc::strong_count(&alive) > 1 {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(5),
+            "workers are still running"
+        );
+        std::thread::yield_now();
+    }
+
+    Ok(())
+}
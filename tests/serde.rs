@@ -571,6 +571,63 @@ fn test_from_value_enum_untagged() -> Result<(), Box<dyn StdError>> {
     Ok(())
 }
 
+#[test]
+fn test_from_value_error_path() -> Result<(), Box<dyn StdError>> {
+    #[derive(Deserialize, Debug)]
+    #[allow(unused)]
+    struct Server {
+        name: String,
+        ports: Vec<u16>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(unused)]
+    struct Config {
+        servers: Vec<Server>,
+        limits: HashMap<String, u32>,
+    }
+
+    let lua = Lua::new();
+
+    let value = lua
+        .load(
+            r#"{
+                servers = {
+                    { name = "a", ports = {80} },
+                    { name = "b", ports = {8080, "http"} },
+                },
+                limits = {},
+            }"#,
+        )
+        .eval()?;
+    match lua.from_value::<Config>(value) {
+        Err(Error::DeserializePathError { path, message }) => {
+            assert_eq!(path, "servers[2].ports[2]");
+            assert!(message.contains("invalid type"), "{message}");
+        }
+        r => panic!("expected DeserializePathError, got {r:?}"),
+    }
+
+    let value = lua
+        .load(r#"{ servers = {}, limits = { ["max conn"] = -1 } }"#)
+        .eval()?;
+    let err = lua.from_value::<Config>(value).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with(r#"deserialize error at `limits["max conn"]`: "#),
+        "{err}"
+    );
+
+    // Errors at the top level have no path
+    let value = lua.load(r#"{ servers = {} }"#).eval()?;
+    match lua.from_value::<Config>(value) {
+        Err(Error::DeserializeError(message)) => assert!(message.contains("limits")),
+        r => panic!("expected DeserializeError, got {r:?}"),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_from_value_with_options() -> Result<(), Box<dyn StdError>> {
     let lua = Lua::new();
@@ -628,6 +685,73 @@ fn test_from_value_with_options() -> Result<(), Box<dyn StdError>> {
     Ok(())
 }
 
+#[test]
+fn test_from_value_strict_options() -> Result<(), Box<dyn StdError>> {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        name: String,
+        port: u16,
+        #[serde(default = "default_ratio")]
+        ratio: f32,
+    }
+
+    fn default_ratio() -> f32 {
+        0.5
+    }
+
+    let lua = Lua::new();
+
+    // Unknown fields are ignored by default
+    let value = lua
+        .load(r#"{name = "a", port = 80, extra = true}"#)
+        .eval::<Value>()?;
+    assert!(lua.from_value::<Config>(value.clone()).is_ok());
+    let options = DeserializeOptions::new().deny_unknown_fields(true);
+    match lua.from_value_with::<Config>(value, options) {
+        Err(Error::DeserializeError(err)) => assert!(err.contains("unknown field `extra`"), "{err}"),
+        r => panic!("expected `DeserializeError` error, got {r:?}"),
+    }
+
+    // Float values are never truncated to integers
+    let value = lua.load(r#"{name = "a", port = 80.5}"#).eval::<Value>()?;
+    assert!(lua.from_value::<Config>(value.clone()).is_err());
+    let options = DeserializeOptions::new().coerce_floats_to_integers(true);
+    assert!(lua.from_value_with::<Config>(value, options).is_err());
+    let value = lua.load(r#"{name = "a", port = 80.0}"#).eval::<Value>()?;
+    #[cfg(any(feature = "lua54", feature = "lua53"))]
+    assert!(lua.from_value::<Config>(value.clone()).is_err());
+    assert_eq!(lua.from_value_with::<Config>(value, options)?.port, 80);
+
+    // String to number coercion
+    let value = lua
+        .load(r#"{name = "a", port = " 8080 ", ratio = "0.25"}"#)
+        .eval::<Value>()?;
+    assert!(lua.from_value::<Config>(value.clone()).is_err());
+    let options = DeserializeOptions::new().coerce_strings_to_numbers(true);
+    let config = lua.from_value_with::<Config>(value, options)?;
+    assert_eq!((config.port, config.ratio), (8080, 0.25));
+    let value = lua.load(r#"{name = "a", port = "80.5"}"#).eval()?;
+    assert!(lua.from_value_with::<Config>(value, options).is_err());
+    let value = lua.load(r#"{name = "a", port = 80, ratio = "nan"}"#).eval()?;
+    assert!(lua.from_value_with::<Config>(value, options).is_err());
+
+    // Number to string coercion
+    let value = lua.load(r#"{name = 123, port = 80}"#).eval::<Value>()?;
+    assert!(lua.from_value::<Config>(value.clone()).is_err());
+    let options = DeserializeOptions::new().coerce_numbers_to_strings(true);
+    assert_eq!(lua.from_value_with::<Config>(value, options)?.name, "123");
+
+    // `null` fields as missing
+    lua.globals().set("null", lua.null())?;
+    let value = lua
+        .load(r#"{name = "a", port = 80, ratio = null}"#)
+        .eval::<Value>()?;
+    assert!(lua.from_value::<Config>(value.clone()).is_err());
+    let options = DeserializeOptions::new().null_fields_as_missing(true);
+    assert_eq!(lua.from_value_with::<Config>(value, options)?.ratio, 0.5);
+
+    Ok(())
+}
 #[test]
 fn test_from_value_userdata() -> Result<(), Box<dyn StdError>> {
     let lua = Lua::new();
@@ -800,3 +924,183 @@ fn test_buffer_from_value() -> LuaResult<()> {
 
     Ok(())
 }
+
+#[cfg(feature = "luau")]
+#[test]
+fn test_bytes_to_buffer() -> LuaResult<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Payload {
+        data: BString,
+        raw: Vec<u8>,
+    }
+
+    let lua = Lua::new();
+
+    let payload = Payload {
+        data: BString::from(b"\x00\x01\xff".as_slice()),
+        raw: vec![1, 2, 3],
+    };
+
+    // Bytes are serialized to strings by default
+    let value = lua.to_value(&payload)?;
+    let t = value.as_table().unwrap();
+    assert!(t.get::<Value>("data")?.is_string());
+
+    let options = SerializeOptions::new().serialize_bytes_to_buffer(true);
+    let value = lua.to_value_with(&payload, options)?;
+    let t = value.as_table().unwrap();
+    let buf = t.get::<Value>("data")?;
+    assert!(buf.is_buffer());
+    assert_eq!(buf.as_buffer().unwrap().to_vec(), b"\x00\x01\xff");
+
+    // Buffers are accepted wherever bytes (or byte sequences) are expected
+    t.set("raw", lua.create_buffer([1, 2, 3])?)?;
+    assert_eq!(lua.from_value::<Payload>(value)?, payload);
+
+    Ok(())
+}
+
+#[test]
+fn test_from_value_userdata_conversion() -> Result<(), Box<dyn StdError>> {
+    #[derive(Clone, Debug, PartialEq, Deserialize)]
+    struct Color(u8, u8, u8);
+
+    impl UserData for Color {
+        fn add_methods<M: mlua::UserDataMethods<Self>>(methods: &mut M) {
+            methods.add_meta_method("__tovalue", |lua, this, ()| {
+                lua.create_sequence_from([this.0, this.1, this.2])
+            });
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Deserialize)]
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+
+    impl UserData for Point {}
+
+    #[derive(Debug, Deserialize)]
+    struct Config {
+        // Converted using `__tovalue`
+        background: Color,
+        #[serde(deserialize_with = "mlua::serde::de::userdata")]
+        foreground: Color,
+        #[serde(deserialize_with = "mlua::serde::de::userdata")]
+        origin: Point,
+        #[serde(deserialize_with = "mlua::serde::de::userdata")]
+        size: Point,
+    }
+
+    let lua = Lua::new();
+    lua.globals().set("red", Color(255, 0, 0))?;
+    lua.globals().set("blue", Color(0, 0, 255))?;
+    lua.globals().set("origin", Point { x: 1.0, y: 2.0 })?;
+
+    let value = lua
+        .load("{background = red, foreground = blue, origin = origin, size = {x = 3, y = 4}}")
+        .eval()?;
+    let config: Config = lua.from_value(value)?;
+    assert_eq!(config.background, Color(255, 0, 0));
+    assert_eq!(config.foreground, Color(0, 0, 255));
+    assert_eq!(config.origin, Point { x: 1.0, y: 2.0 });
+    assert_eq!(config.size, Point { x: 3.0, y: 4.0 });
+
+    // Userdata without `__tovalue` cannot be deserialized into a different type
+    let value = lua
+        .load("{background = red, foreground = blue, origin = red, size = origin}")
+        .eval()?;
+    assert!(lua.from_value::<Config>(value).is_err());
+
+    // `__tovalue` returning the same userdata
+    struct Looped;
+
+    impl UserData for Looped {
+        fn add_methods<M: mlua::UserDataMethods<Self>>(methods: &mut M) {
+            methods.add_meta_function("__tovalue", |lua, ud: mlua::AnyUserData| {
+                lua.create_sequence_from([ud])
+            });
+        }
+    }
+
+    let value = Value::UserData(lua.create_userdata(Looped)?);
+    match lua.from_value::<Vec<u8>>(value) {
+        Err(Error::DeserializePathError { message, .. }) => {
+            assert!(message.contains("recursive `__tovalue`"), "{message}")
+        }
+        r => panic!("expected `DeserializePathError` error, got {r:?}"),
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_module() -> LuaResult<()> {
+    let lua = Lua::new();
+    mlua::serde::json::register(&lua)?;
+    let json: mlua::Table = lua
+        .load(format!("require('{}')", mlua::serde::json::MODULE_NAME))
+        .eval()?;
+    lua.globals().set("json", json)?;
+    lua.globals().set("null", lua.null())?;
+
+    lua.load(
+        r#"
+        local s = json.encode({b = {1, 2, 3}, a = null, c = "x"}, {sort_keys = true})
+        assert(s == '{"a":null,"b":[1,2,3],"c":"x"}', s)
+
+        local pretty = json.encode({1}, {pretty = true})
+        assert(pretty == "[\n  1\n]", pretty)
+
+        local v = json.decode('{"arr": [], "n": null, "f": 1.5, "i": 42}')
+        assert(v.n == null and v.f == 1.5 and v.i == 42)
+        assert(json.encode(v.arr) == "[]")
+
+        assert(not pcall(json.decode, '{"a": 1} x'))
+    "#,
+    )
+    .exec()
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn test_msgpack_module() -> LuaResult<()> {
+    let lua = Lua::new();
+    lua.globals()
+        .set("msgpack", mlua::serde::msgpack::create_module(&lua)?)?;
+    lua.globals().set("null", lua.null())?;
+
+    lua.load(
+        r#"
+        local data = msgpack.encode({a = 1, b = {true, "x", null}, c = {}}, {sort_keys = true})
+        local v = msgpack.decode(data)
+        assert(v.a == 1 and v.b[1] == true and v.b[2] == "x" and v.b[3] == null)
+        assert(msgpack.encode(v, {sort_keys = true}) == data)
+        assert(msgpack.encode({}) == "\128")
+        assert(msgpack.encode(msgpack.decode("\144")) == "\144")
+    "#,
+    )
+    .exec()
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn test_cbor_module() -> LuaResult<()> {
+    let lua = Lua::new();
+    lua.globals()
+        .set("cbor", mlua::serde::cbor::create_module(&lua)?)?;
+    lua.globals().set("null", lua.null())?;
+
+    lua.load(
+        r#"
+        local data = cbor.encode({a = -5, b = {1.5, "\255", null}}, {sort_keys = true})
+        local v = cbor.decode(data)
+        assert(v.a == -5 and v.b[1] == 1.5 and v.b[2] == "\255" and v.b[3] == null)
+        assert(cbor.encode(v, {sort_keys = true}) == data)
+        assert(not pcall(cbor.decode, data .. "\0"))
+    "#,
+    )
+    .exec()
+}
@@ -157,3 +157,114 @@ fn test_bytes_into_iter() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_string_builder() -> Result<()> {
+    use std::io::Write as _;
+
+    let lua = Lua::new();
+
+    let builder = lua.create_string_builder()?;
+    assert!(builder.is_empty());
+    assert_eq!(builder.finish()?, "");
+
+    let mut builder = lua.create_string_builder()?;
+    builder.push("hello")?;
+    builder.push(b", \0")?;
+    std::fmt::Write::write_fmt(&mut builder, format_args!("{}", 123)).unwrap();
+    builder.write_all(b" world").unwrap();
+    assert_eq!(builder.len(), 17);
+    assert_eq!(builder.finish()?, b"hello, \x00123 world".as_slice());
+
+    // Build a string larger than the internal buffer while Lua is in use
+    let mut builder = lua.create_string_builder()?;
+    let mut expected = Vec::new();
+    for i in 0..10000 {
+        let chunk = format!("{i},");
+        builder.push(&chunk)?;
+        expected.extend_from_slice(chunk.as_bytes());
+        if i % 1000 == 0 {
+            lua.load("local t = {} for i = 1, 100 do t[i] = tostring(i) end")
+                .exec()?;
+            lua.gc_collect()?;
+        }
+    }
+    let s = builder.finish()?;
+    assert_eq!(s.as_bytes(), expected.as_slice());
+    lua.globals().set("s", s)?;
+    assert_eq!(lua.load("#s").eval::<usize>()?, expected.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_lua_bytes() -> Result<()> {
+    use mlua::LuaBytes;
+
+    let lua = Lua::new();
+
+    let s = lua.create_string(b"hello\0world")?;
+    let bytes = s.to_lua_bytes()?;
+    assert_eq!(bytes, b"hello\0world");
+    assert_eq!(bytes.len(), 11);
+    drop(s);
+    lua.gc_collect()?;
+
+    // Cloning is cheap and data is pinned
+    let bytes2 = bytes.clone();
+    assert_eq!(bytes.as_ptr(), bytes2.as_ptr());
+    // `LuaBytes` has interior mutability (the state pin), but hashes only the immutable bytes
+    #[allow(clippy::mutable_key_type)]
+    let mut set = HashSet::new();
+    set.insert(bytes.clone());
+    assert!(set.contains(&lua.create_string("hello\0world")?.to_lua_bytes()?));
+
+    // Conversion to/from Lua
+    lua.globals().set("b", &bytes)?;
+    assert_eq!(lua.load("b").eval::<String>()?, b"hello\0world");
+    let from_lua: LuaBytes = lua.load("'abc' .. 123").eval()?;
+    assert_eq!(from_lua, "abc123");
+
+    // Bytes do not keep the Lua instance alive, but remain valid after it's dropped
+    let weak = lua.weak();
+    drop(lua);
+    assert!(weak.try_upgrade().is_none());
+    assert_eq!(bytes2, b"hello\0world");
+
+    Ok(())
+}
+
+#[test]
+fn test_lua_bytes_close_state() -> Result<()> {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    struct MyUserData(Arc<AtomicBool>);
+
+    impl mlua::UserData for MyUserData {}
+
+    impl Drop for MyUserData {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let lua = Lua::new();
+    let closed = Arc::new(AtomicBool::new(false));
+    lua.globals().set("ud", MyUserData(closed.clone()))?;
+    let bytes = lua.create_string("hello")?.to_lua_bytes()?;
+    let bytes2 = bytes.clone();
+
+    // The state is kept open while any handle is alive
+    drop(lua);
+    assert!(!closed.load(Ordering::Relaxed));
+    drop(bytes);
+    assert!(!closed.load(Ordering::Relaxed));
+    assert_eq!(bytes2, b"hello");
+
+    // And closed once the last handle is dropped
+    drop(bytes2);
+    assert!(closed.load(Ordering::Relaxed));
+
+    Ok(())
+}
@@ -1,4 +1,6 @@
-use mlua::{Error, Lua, ObjectLike, Result, Table, Value};
+use mlua::{
+    Error, FieldType, FromLua, Lua, ObjectLike, Result, Table, TableSchema, Typed, TypedTable, Value,
+};
 
 #[test]
 fn test_globals_set_get() -> Result<()> {
@@ -93,7 +95,7 @@ fn test_table_push_pop() -> Result<()> {
     assert_eq!(table2.len()?, 2);
     assert_eq!(
         table2.sequence_values::<i64>().collect::<Result<Vec<_>>>()?,
-        vec![]
+        Vec::<i64>::new()
     );
     assert_eq!(table2.pop::<i64>()?, 345);
     assert_eq!(table2.pop::<i64>()?, 234);
@@ -103,6 +105,42 @@ fn test_table_push_pop() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_table_batch_ops() -> Result<()> {
+    let lua = Lua::new();
+
+    let table = lua.create_sequence_from([1, 2])?;
+    table.extend_sequence(3..=5)?;
+    assert_eq!(table.to_vec::<i64>()?, vec![1, 2, 3, 4, 5]);
+    table.extend_sequence(Vec::<i64>::new())?;
+    assert_eq!(table.raw_len(), 5);
+
+    table.set_many([("a", 10), ("b", 20)])?;
+    assert_eq!(
+        table.get_many::<_, Option<i64>>(["a", "b", "c"])?,
+        vec![Some(10), Some(20), None]
+    );
+    assert!(table.to_vec::<String>().is_ok());
+
+    // Test access through metamethods
+    let proxy = lua
+        .load(
+            r#"
+        local proxy_table = {x = 1}
+        return setmetatable({}, {
+            __index = proxy_table,
+            __newindex = function(_, k, v) rawset(proxy_table, k, v * 2) end,
+        })
+    "#,
+        )
+        .eval::<Table>()?;
+    proxy.set_many([("y", 2), ("z", 3)])?;
+    assert_eq!(proxy.get_many::<_, i64>(["x", "y", "z"])?, vec![1, 4, 6]);
+    assert_eq!(proxy.raw_len(), 0);
+
+    Ok(())
+}
+
 #[test]
 fn test_table_insert_remove() -> Result<()> {
     let lua = Lua::new();
@@ -473,3 +511,218 @@ fn test_table_object_like() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_table_schema() -> Result<()> {
+    let lua = Lua::new();
+
+    let server = TableSchema::new()
+        .field("host", FieldType::String)
+        .field_with_default("port", FieldType::Integer, 80)
+        .deny_unknown_fields(true);
+    let schema = TableSchema::new()
+        .field("name", FieldType::String)
+        .optional_field("debug", FieldType::Boolean)
+        .field("servers", FieldType::array(FieldType::Schema(server)))
+        .optional_field("env", FieldType::map(FieldType::String, FieldType::String))
+        .optional_field(
+            "level",
+            FieldType::custom(|v| match v.as_integer() {
+                Some(1..=5) => Ok(()),
+                _ => Err("expected level between 1 and 5".into()),
+            }),
+        );
+
+    let config: Table = lua
+        .load(r#"{name = "app", servers = {{host = "a"}, {host = "b", port = 8080}}}"#)
+        .eval()?;
+    schema.validate(&config)?;
+    assert_eq!(
+        config
+            .get::<Table>("servers")?
+            .get::<Table>(1)?
+            .get::<Value>("port")?,
+        Value::Nil
+    );
+    schema.apply(&config)?;
+    assert_eq!(
+        config
+            .get::<Table>("servers")?
+            .get::<Table>(1)?
+            .get::<i64>("port")?,
+        80
+    );
+
+    let check_err = |code: &str, expected: &str| -> Result<()> {
+        let config: Table = lua.load(code).eval()?;
+        match schema.validate(&config) {
+            Err(err @ Error::SchemaError { .. }) => assert_eq!(err.to_string(), expected),
+            res => panic!("expected schema error, got {res:?}"),
+        }
+        Ok(())
+    };
+    check_err("{servers = {}}", "schema error at `name`: missing required field")?;
+    check_err(
+        r#"{name = "app", servers = {{host = "a", port = 1.5}}}"#,
+        "schema error at `servers[1].port`: expected integer, got number",
+    )?;
+    check_err(
+        r#"{name = "app", servers = {{host = "a", extra = true}}}"#,
+        "schema error at `servers[1].extra`: unknown field",
+    )?;
+    check_err(
+        r#"{name = "app", servers = {}, env = {["my-var"] = 1}}"#,
+        r#"schema error at `env["my-var"]`: expected string, got integer"#,
+    )?;
+    check_err(
+        r#"{name = "app", servers = {}, level = 10}"#,
+        "schema error at `level`: expected level between 1 and 5",
+    )?;
+
+    Ok(())
+}
+
+#[test]
+fn test_typed_table() -> Result<()> {
+    #[derive(Debug)]
+    struct Config {
+        name: String,
+        retries: u32,
+    }
+
+    impl FromLua for Config {
+        fn from_lua(value: Value, _: &Lua) -> Result<Self> {
+            let table = value.as_table().unwrap();
+            Ok(Config {
+                name: table.get("name")?,
+                retries: table.get("retries")?,
+            })
+        }
+    }
+
+    impl TypedTable for Config {
+        fn schema() -> TableSchema {
+            TableSchema::new()
+                .field("name", FieldType::String)
+                .field_with_default("retries", FieldType::Integer, 3)
+        }
+    }
+
+    let lua = Lua::new();
+
+    let Typed(config) = lua.load(r#"{name = "job"}"#).eval::<Typed<Config>>()?;
+    assert_eq!(config.name, "job");
+    assert_eq!(config.retries, 3);
+
+    let err = lua.load(r#"{retries = 1}"#).eval::<Typed<Config>>().unwrap_err();
+    assert_eq!(err.to_string(), "schema error at `name`: missing required field");
+    assert!(lua.load("123").eval::<Typed<Config>>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_table_cursor() -> Result<()> {
+    let lua = Lua::new();
+
+    let table = lua.create_table()?;
+    for i in 1..=2500 {
+        table.raw_set(format!("key{i}"), i)?;
+    }
+
+    let mut cursor = table.cursor();
+    let mut sum = 0;
+    let mut batches = 0;
+    while !cursor.is_finished() {
+        let batch = cursor.next_batch::<String, i64>(1000)?;
+        assert!(batch.len() <= 1000);
+        sum += batch.into_iter().map(|(_, v)| v).sum::<i64>();
+        batches += 1;
+        // Modifying values of existing keys between batches is allowed
+        lua.load("collectgarbage()").exec()?;
+    }
+    assert_eq!(sum, (1..=2500).sum::<i64>());
+    assert!(batches >= 3);
+    assert!(cursor.next_batch::<Value, Value>(10)?.is_empty());
+
+    // Removing the current key is detected
+    cursor.reset();
+    let batch = cursor.next_batch::<String, i64>(1)?;
+    table.raw_set(batch[0].0.as_str(), Value::Nil)?;
+    match cursor.next_batch::<String, i64>(1) {
+        Err(Error::RuntimeError(msg)) => assert!(msg.contains("modified during iteration")),
+        r => panic!("expected RuntimeError, got {r:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_table_sort_and_helpers() -> Result<()> {
+    let lua = Lua::new();
+
+    let table = lua.load(r#"{3, "b", 1.5, true, "a", 2}"#).eval::<Table>()?;
+    table.sort()?;
+    assert_eq!(
+        table.to_vec::<Value>()?,
+        vec![
+            Value::Boolean(true),
+            Value::Number(1.5),
+            Value::Integer(2),
+            Value::Integer(3),
+            Value::String(lua.create_string("a")?),
+            Value::String(lua.create_string("b")?),
+        ]
+    );
+
+    let table = lua.create_sequence_from([5, 3, 8, 1])?;
+    table.sort_by(|a, b| b.as_integer().cmp(&a.as_integer()))?;
+    assert_eq!(table.to_vec::<i64>()?, vec![8, 5, 3, 1]);
+
+    // Larger (odd-sized) sequences of tables are sorted stably
+    let items = (0..1001)
+        .map(|i| ((i * 7919) % 13, i))
+        .collect::<Vec<(i64, i64)>>();
+    let tables = items
+        .iter()
+        .map(|&(key, id)| lua.create_table_from([("key", key), ("id", id)]));
+    let seq = lua.create_sequence_from(tables.collect::<Result<Vec<_>>>()?)?;
+    let key = |v: &Value| v.as_table().unwrap().raw_get::<i64>("key").unwrap();
+    seq.sort_by(|a, b| key(a).cmp(&key(b)))?;
+    let mut expected = items;
+    expected.sort_by_key(|&(key, _)| key);
+    let sorted = seq
+        .to_vec::<Table>()?
+        .iter()
+        .map(|t| Ok((t.raw_get("key")?, t.raw_get("id")?)))
+        .collect::<Result<Vec<(i64, i64)>>>()?;
+    assert_eq!(sorted, expected);
+
+    table.retain(|v: i64| Ok(v % 2 == 1))?;
+    assert_eq!(table.to_vec::<i64>()?, vec![5, 3, 1]);
+    assert_eq!(table.raw_len(), 3);
+    assert_eq!(table.raw_get::<Value>(4)?, Value::Nil);
+
+    // On error the sequence is left contiguous
+    let seq = lua.create_sequence_from([1, 2, 3, 4, 5])?;
+    let res = seq.retain(|v: i64| match v {
+        4 => Err(Error::runtime("stop")),
+        v => Ok(v != 2),
+    });
+    assert!(res.is_err());
+    assert_eq!(seq.to_vec::<i64>()?, vec![1, 3, 4, 5]);
+    assert_eq!(seq.raw_len(), 4);
+
+    table.map_in_place(|v: i64| Ok(v * 10))?;
+    assert_eq!(table.to_vec::<i64>()?, vec![50, 30, 10]);
+    assert!(table.map_in_place(|_: Value| Ok(Value::Nil)).is_err());
+
+    table.sort()?;
+    let search = |target: i64| table.binary_search_by(|v| v.as_integer().unwrap().cmp(&target));
+    assert_eq!(search(30)?, Ok(2));
+    assert_eq!(search(5)?, Err(1));
+    assert_eq!(search(40)?, Err(3));
+    assert_eq!(search(100)?, Err(4));
+
+    Ok(())
+}
@@ -7,8 +7,8 @@ use std::sync::Arc;
 use std::{error, f32, f64, fmt};
 
 use mlua::{
-    ffi, ChunkMode, Error, ExternalError, Function, Lua, LuaOptions, Nil, Result, StdLib, String, Table,
-    UserData, Value, Variadic,
+    ffi, ChunkMode, Error, ExternalError, Function, Lua, LuaOptions, LuaPool, LuaPoolOptions, Nil,
+    ResetStrategy, Result, StdLib, String, Table, UserData, Value, Variadic,
 };
 
 #[test]
@@ -1505,3 +1505,136 @@ fn test_get_or_init_from_ptr() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_interrupt_handle() -> Result<()> {
+    use std::time::Duration;
+
+    let lua = Lua::new();
+    let handle = lua.interrupt_handle();
+    assert!(!handle.is_interrupted());
+
+    let watchdog = {
+        let handle = handle.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            handle.interrupt();
+        })
+    };
+    // The interrupt cannot be caught by `pcall`
+    let result = lua
+        .load("while true do pcall(function() while true do end end) end")
+        .exec();
+    watchdog.join().unwrap();
+    assert!(matches!(result, Err(Error::Interrupted)), "{result:?}");
+    assert!(handle.is_interrupted());
+
+    // The request is sticky until reset
+    let result = lua.load("for i = 1, 1000000 do end").exec();
+    assert!(matches!(result, Err(Error::Interrupted)));
+    handle.reset();
+    lua.load("for i = 1, 1000000 do end").exec()?;
+
+    // Interrupt a specific thread
+    let thread = lua.create_thread(lua.load("for i = 1, 1000000 do end").into_function()?)?;
+    let thread_handle = thread.interrupt_handle()?;
+    thread_handle.interrupt();
+    lua.load("for i = 1, 1000000 do end").exec()?;
+    assert!(matches!(thread.resume::<()>(()), Err(Error::Interrupted)));
+
+    // Dropping the handle clears the request
+    drop(thread_handle);
+    lua.load("for i = 1, 1000000 do end").exec()?;
+
+    // Resetting the thread detaches its handles
+    let func = lua.load("for i = 1, 1000000 do end").into_function()?;
+    let thread = lua.create_thread(func.clone())?;
+    let thread_handle = thread.interrupt_handle()?;
+    thread.reset(func)?;
+    thread_handle.interrupt();
+    assert!(!thread_handle.is_interrupted());
+    thread.resume::<()>(())?;
+
+    Ok(())
+}
+
+#[test]
+fn test_lua_pool() -> Result<()> {
+    let pool = LuaPool::new(|| Ok(Lua::new()), LuaPoolOptions::new().max_size(2));
+
+    // Globals are restored between checkouts
+    {
+        let lua = pool.checkout()?;
+        lua.load("polluted = true; print = nil").exec()?;
+    }
+    {
+        let lua = pool.checkout()?;
+        assert_eq!(lua.globals().get::<Option<bool>>("polluted")?, None);
+        assert!(lua.globals().get::<Function>("print").is_ok());
+
+        // Metatable of the globals table is restored too
+        lua.load("setmetatable(_G, { __index = function() return 1 end })")
+            .exec()?;
+    }
+    {
+        let lua = pool.checkout()?;
+        assert_eq!(lua.globals().get::<Option<i32>>("missing")?, None);
+        assert!(lua.globals().metatable().is_none());
+    }
+    let stats = pool.stats();
+    assert_eq!(
+        (stats.created, stats.reused, stats.idle, stats.active),
+        (1, 2, 1, 0)
+    );
+
+    // Bounded size
+    let (lua1, lua2) = (pool.checkout()?, pool.checkout()?);
+    match pool.checkout() {
+        Err(Error::RuntimeError(msg)) => assert_eq!(msg, "Lua pool is exhausted"),
+        r => panic!("expected RuntimeError, got {r:?}"),
+    }
+    lua2.discard();
+    drop(lua1);
+    let stats = pool.stats();
+    assert_eq!((stats.created, stats.discarded, stats.idle), (2, 1, 1));
+
+    // Recreate strategy never reuses states
+    let options = LuaPoolOptions::new().reset(ResetStrategy::Recreate);
+    let pool = LuaPool::new(|| Ok(Lua::new()), options);
+    drop(pool.checkout()?);
+    drop(pool.checkout()?);
+    let stats = pool.stats();
+    assert_eq!((stats.created, stats.reused, stats.discarded), (2, 0, 2));
+
+    // Unhealthy states are discarded
+    let options = LuaPoolOptions::new().max_memory(1024 * 1024);
+    let pool = LuaPool::new(|| Ok(Lua::new()), options);
+    {
+        let lua = pool.checkout()?;
+        lua.load("leak = string.rep('x', 2 * 1024 * 1024)").exec()?;
+        // Reachable from the registry, so survives the globals reset
+        let leak: String = lua.globals().get("leak")?;
+        lua.set_named_registry_value("leak", leak)?;
+    }
+    assert_eq!(pool.stats().discarded, 1);
+
+    #[cfg(feature = "luau")]
+    {
+        let options = LuaPoolOptions::new().reset(ResetStrategy::Sandbox);
+        let pool = LuaPool::new(|| Ok(Lua::new()), options);
+        {
+            let lua = pool.checkout()?;
+            lua.load("polluted = true").exec()?;
+            assert!(lua.load("string.foo = 1").exec().is_err());
+        }
+        let lua = pool.checkout()?;
+        assert_eq!(lua.globals().get::<Option<bool>>("polluted")?, None);
+    }
+
+    // Factory errors are propagated
+    let pool = LuaPool::new(|| Err(Error::runtime("factory error")), LuaPoolOptions::new());
+    assert!(pool.checkout().is_err());
+    assert_eq!(pool.stats().active, 0);
+
+    Ok(())
+}
@@ -808,3 +808,403 @@ fn test_large_thread_creation() {
         assert!(v.contains("Reached continuation which should panic!"));
     }
 }
+
+#[cfg(feature = "scheduler")]
+#[test]
+fn test_scheduler() -> Result<()> {
+    use std::time::{Duration, Instant};
+
+    use mlua::{Scheduler, Table};
+
+    let lua = Lua::new();
+    let scheduler = Scheduler::new(&lua)?;
+    scheduler.install()?;
+
+    lua.load(
+        r#"
+        log = {}
+        task.spawn(function()
+            table.insert(log, "spawn")
+            task.wait()
+            table.insert(log, "spawn resumed")
+        end)
+        task.defer(function(a) table.insert(log, "defer " .. a) end, 1)
+        task.delay(1, function() table.insert(log, "delay") end)
+        cancelled = task.delay(1, function() table.insert(log, "cancelled") end)
+        task.cancel(cancelled)
+        task.defer(function() error("boom") end)
+        "#,
+    )
+    .exec()?;
+    assert_eq!(scheduler.task_count(), 4);
+
+    let log = || -> Result<Vec<String>> { lua.globals().get::<Table>("log")?.sequence_values().collect() };
+    assert_eq!(log()?, vec!["spawn"]);
+
+    let start = Instant::now();
+    assert_eq!(scheduler.step(start)?, 3);
+    assert_eq!(log()?, vec!["spawn", "spawn resumed", "defer 1"]);
+    let errors = scheduler.take_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].1.to_string().contains("boom"));
+    assert_eq!(errors[0].0.status(), ThreadStatus::Error);
+
+    assert_eq!(scheduler.step(start + Duration::from_millis(500))?, 0);
+    assert_eq!(scheduler.next_wakeup(), Some(start + Duration::from_secs(1)));
+    assert_eq!(scheduler.step(start + Duration::from_secs(1))?, 1);
+    assert_eq!(log()?, vec!["spawn", "spawn resumed", "defer 1", "delay"]);
+    assert!(scheduler.is_idle());
+
+    // `task.wait` returns the elapsed time
+    let func = lua
+        .load("function() local elapsed = task.wait(0.5); elapsed_time = elapsed end")
+        .eval::<Function>()?;
+    scheduler.spawn(func, ())?;
+    let now = start + Duration::from_secs(2);
+    assert_eq!(scheduler.step(now)?, 1);
+    assert_eq!(scheduler.step(now + Duration::from_secs(2))?, 1);
+    assert_eq!(lua.globals().get::<f64>("elapsed_time")?, 2.0);
+
+    // Tasks yielding with `coroutine.yield` are resumed on the next step
+    lua.load("task.spawn(function() coroutine.yield(); yielded = true end)")
+        .exec()?;
+    assert_eq!(scheduler.task_count(), 1);
+    assert_eq!(scheduler.step(now + Duration::from_secs(2))?, 1);
+    assert!(lua.globals().get::<bool>("yielded")?);
+
+    // `task.wait` outside of a task
+    assert!(lua.load("task.wait(1)").exec().is_err());
+    assert!(scheduler.is_idle());
+
+    // Run with real time
+    lua.load("task.spawn(function() for _ = 1, 3 do task.wait(0.001) end; done = true end)")
+        .exec()?;
+    scheduler.run_until_idle()?;
+    assert!(lua.globals().get::<bool>("done")?);
+
+    Ok(())
+}
+
+#[cfg(feature = "scheduler")]
+#[test]
+fn test_scheduler_drop() -> Result<()> {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use mlua::{Scheduler, UserData};
+
+    struct Guard(Arc<AtomicBool>);
+
+    impl UserData for Guard {}
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let lua = Lua::new();
+    let scheduler = Scheduler::new(&lua)?;
+    scheduler.install()?;
+
+    let dropped = Arc::new(AtomicBool::new(false));
+    let func = lua
+        .load("function(guard) task.wait(10); print(guard) end")
+        .eval::<Function>()?;
+    scheduler.spawn(func, Guard(dropped.clone()))?;
+    scheduler.step(std::time::Instant::now())?;
+    assert_eq!(scheduler.task_count(), 1);
+
+    // Sleeping tasks are released with the scheduler, even though `task` is still a global
+    drop(scheduler);
+    lua.gc_collect()?;
+    lua.gc_collect()?;
+    assert!(dropped.load(Ordering::Relaxed));
+
+    let err = lua.load("task.defer(print)").exec().unwrap_err();
+    assert!(err.to_string().contains("task scheduler is dropped"));
+
+    Ok(())
+}
+
+#[test]
+fn test_thread_app_data() -> Result<()> {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    struct Context {
+        user: &'static str,
+        dropped: Arc<AtomicBool>,
+    }
+
+    impl Drop for Context {
+        fn drop(&mut self) {
+            self.dropped.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let lua = Lua::new();
+    let whoami = lua.create_function(|lua, ()| {
+        let user = lua.current_thread_data::<Context>().map(|ctx| ctx.user);
+        if let Some(mut ctx) = lua.current_thread_data_mut::<Context>() {
+            ctx.user = "seen";
+        }
+        Ok(user)
+    })?;
+    lua.globals().set("whoami", whoami)?;
+
+    let func: Function = lua
+        .load("function() local user = whoami(); coroutine.yield(); return user, whoami() end")
+        .eval()?;
+
+    let dropped = Arc::new(AtomicBool::new(false));
+    let thread = lua.create_thread(func.clone())?;
+    let ctx = Context {
+        user: "alice",
+        dropped: dropped.clone(),
+    };
+    assert!(thread.set_app_data(ctx)?.is_none());
+    assert_eq!(thread.app_data_ref::<Context>().unwrap().user, "alice");
+
+    // Other threads (and the main thread) do not see the data
+    let thread2 = lua.create_thread(func)?;
+    assert!(thread2.app_data_ref::<Context>().is_none());
+    assert!(lua.current_thread_data::<Context>().is_none());
+    thread2.resume::<()>(())?;
+    assert_eq!(
+        thread2.resume::<(Option<String>, Option<String>)>(())?,
+        (None, None)
+    );
+
+    thread.resume::<()>(())?;
+    let (first, second) = thread.resume::<(Option<String>, Option<String>)>(())?;
+    assert_eq!(first.as_deref(), Some("alice"));
+    assert_eq!(second.as_deref(), Some("seen"));
+
+    // Data is dropped when the thread is collected
+    assert!(!dropped.load(Ordering::Relaxed));
+    drop(thread);
+    lua.gc_collect()?;
+    lua.gc_collect()?;
+    assert!(dropped.load(Ordering::Relaxed));
+
+    // Removing data
+    let thread = lua.create_thread(lua.create_function(|_, ()| Ok(()))?)?;
+    thread.set_app_data(42u32)?;
+    assert_eq!(thread.remove_app_data::<u32>(), Some(42));
+    assert!(thread.app_data_ref::<u32>().is_none());
+
+    // Data of live threads is dropped when the Lua state is closed
+    let dropped = Arc::new(AtomicBool::new(false));
+    let thread = lua.create_thread(lua.create_function(|_, ()| Ok(()))?)?;
+    let user = "bob";
+    thread.set_app_data(Context {
+        user,
+        dropped: dropped.clone(),
+    })?;
+    lua.globals().set("thread", thread)?;
+    drop(lua);
+    assert!(dropped.load(Ordering::Relaxed));
+
+    Ok(())
+}
+
+#[test]
+fn test_thread_inspect_stack() -> Result<()> {
+    let lua = Lua::new();
+
+    let thread: Thread = lua
+        .load(
+            r#"
+            coroutine.create(function()
+                local function park()
+                    coroutine.yield()
+                end
+                park()
+                error("failed here")
+            end)
+            "#,
+        )
+        .set_name("@thread.lua")
+        .eval()?;
+
+    // Not started
+    assert!(thread.inspect_stack(0).is_none());
+
+    thread.resume::<()>(())?;
+    let debug = (0..)
+        .map_while(|level| thread.inspect_stack(level))
+        .find(|debug| debug.names().name.as_deref() == Some("park"))
+        .expect("`park` frame not found");
+    assert_eq!(debug.curr_line(), 4);
+    drop(debug);
+
+    let traceback = thread.traceback()?;
+    assert!(traceback.contains("park"), "{traceback}");
+    assert!(traceback.contains("thread.lua:4"), "{traceback}");
+
+    // Errored thread keeps its stack
+    assert!(thread.resume::<()>(()).is_err());
+    let traceback = thread.traceback()?;
+    assert!(traceback.contains("thread.lua:7"), "{traceback}");
+
+    Ok(())
+}
+
+#[test]
+fn test_thread_generator() -> Result<()> {
+    let lua = Lua::new();
+
+    let range: Function = lua
+        .load("function(n) for i = 1, n do coroutine.yield(i, i * i) end return 'done' end")
+        .eval()?;
+    let mut generator = range.as_generator::<(i64, i64)>()?;
+    assert_eq!(generator.send(3).transpose()?, Some((1, 1)));
+    assert_eq!(
+        generator.by_ref().collect::<Result<Vec<_>>>()?,
+        vec![(2, 4), (3, 9)]
+    );
+    assert!(generator.next().is_none());
+    assert_eq!(generator.thread().status(), ThreadStatus::Finished);
+
+    // Passing values back into the coroutine
+    let thread: Thread = lua
+        .load(
+            r#"
+            coroutine.create(function()
+                local sum = 0
+                while true do
+                    sum = sum + coroutine.yield(sum)
+                end
+            end)
+            "#,
+        )
+        .eval()?;
+    let mut generator = thread.into_generator::<i64>();
+    assert_eq!(generator.next().transpose()?, Some(0));
+    assert_eq!(generator.send(5).transpose()?, Some(5));
+    assert_eq!(generator.send(10).transpose()?, Some(15));
+
+    // Errors are returned as items and stop the iteration
+    let func: Function = lua
+        .load("function() coroutine.yield(1) error('boom') end")
+        .eval()?;
+    let mut generator = func.as_generator::<i64>()?;
+    assert_eq!(generator.next().transpose()?, Some(1));
+    match generator.next() {
+        Some(Err(err)) => assert!(err.to_string().contains("boom")),
+        other => panic!("expected error, got {other:?}"),
+    }
+    assert!(generator.next().is_none());
+
+    // A generator over a finished thread ends immediately
+    let thread = lua.create_thread(lua.create_function(|_, ()| Ok(1))?)?;
+    thread.resume::<()>(())?;
+    assert!(thread.into_generator::<i64>().next().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_thread_pool() -> Result<()> {
+    let lua = Lua::new();
+
+    let handler = lua.create_function(|lua, n: i64| {
+        // Application data must not leak between uses
+        let seen = lua.current_thread_data::<i64>().map(|n| *n);
+        lua.current_thread().set_app_data(n)?;
+        Ok((n * 2, seen))
+    })?;
+    let pool = lua.create_thread_pool(handler, 2);
+    assert_eq!(pool.max_size(), 2);
+
+    for n in 0..5 {
+        let thread = pool.acquire()?;
+        assert_eq!(thread.resume::<(i64, Option<i64>)>(n)?, (n * 2, None));
+        assert_eq!(thread.status(), ThreadStatus::Finished);
+    }
+    let stats = pool.stats();
+    assert_eq!(
+        (stats.created, stats.reused, stats.recycled, stats.idle),
+        (1, 4, 5, 1)
+    );
+
+    // Bounded size
+    let threads = (0..3).map(|_| pool.acquire()).collect::<Result<Vec<_>>>()?;
+    drop(threads);
+    let stats = pool.stats();
+    assert_eq!((stats.created, stats.idle, stats.discarded), (3, 2, 1));
+
+    // Detached threads are not returned
+    let thread = pool.acquire()?.detach();
+    assert_eq!(thread.resume::<(i64, Option<i64>)>(1)?, (2, None));
+    assert_eq!(pool.stats().idle, 1);
+
+    // Suspended threads can be recycled on Lua 5.4 and Luau only
+    let func: Function = lua.load("function() coroutine.yield() end").eval()?;
+    let pool = lua.create_thread_pool(func, 4);
+    {
+        let thread = pool.acquire()?;
+        thread.resume::<()>(())?;
+        assert_eq!(thread.status(), ThreadStatus::Resumable);
+    }
+    let recycled = cfg!(any(feature = "lua54", feature = "luau"));
+    assert_eq!(pool.stats().recycled, recycled as usize);
+    assert_eq!(pool.stats().discarded, !recycled as usize);
+
+    // And so are threads stopped by an error
+    let func: Function = lua
+        .load("function(fail) if fail then error('boom') end return 'ok' end")
+        .eval()?;
+    let pool = lua.create_thread_pool(func, 4);
+    {
+        let thread = pool.acquire()?;
+        assert!(thread.resume::<()>(true).is_err());
+        assert_eq!(thread.status(), ThreadStatus::Error);
+    }
+    assert_eq!(pool.stats().recycled, recycled as usize);
+    assert_eq!(pool.stats().discarded, !recycled as usize);
+    if recycled {
+        assert_eq!(pool.acquire()?.resume::<String>(false)?, "ok");
+        assert_eq!(pool.stats().reused, 1);
+    }
+
+    pool.clear();
+    assert_eq!(pool.stats().idle, 0);
+
+    // Interrupt handles of returned threads do not affect the next borrower
+    let func: Function = lua
+        .load("function(n) for _ = 1, 100000 do end return n end")
+        .eval()?;
+    let pool = lua.create_thread_pool(func, 1);
+    let handle = pool.acquire()?.interrupt_handle()?;
+    handle.interrupt();
+    assert_eq!(pool.acquire()?.resume::<i64>(3)?, 3);
+    assert_eq!(pool.stats().reused, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_thread_pool_outlives_lua() -> Result<()> {
+    let lua = Lua::new();
+    let pool = lua.create_thread_pool(lua.create_function(|_, ()| Ok(()))?, 4);
+    let thread = pool.acquire()?;
+    drop(lua);
+
+    // The thread is discarded instead of being recycled
+    drop(thread);
+    let stats = pool.stats();
+    assert_eq!((stats.recycled, stats.discarded, stats.idle), (0, 1, 0));
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "Lua instance passed Function created from a different main Lua state")]
+fn test_thread_pool_different_lua() {
+    let lua = Lua::new();
+    let lua2 = Lua::new();
+    let func = lua2.create_function(|_, ()| Ok(())).unwrap();
+    let _ = lua.create_thread_pool(func, 4);
+}
@@ -135,3 +135,40 @@ fn test_thread_type_metatable() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(all(feature = "userdata-vector", not(feature = "luau")))]
+#[test]
+fn test_userdata_vector() -> Result<()> {
+    use mlua::Vector;
+
+    let lua = Lua::new();
+
+    let f = lua
+        .load(
+            r#"
+            function(a, b)
+                assert(a.x == 1 and a.y == 2 and a.z == 3)
+                assert(a == a and a ~= b)
+                assert(tostring(-a) == "vector(-1, -2, -3)")
+                return a + b, a * 2, 2 * a, a:cross(b), a:dot(b)
+            end
+        "#,
+        )
+        .eval::<Function>()?;
+    let a = Vector::new(1.0, 2.0, 3.0);
+    let b = Vector::new(3.0, 2.0, 1.0);
+    let (sum, mul, mul2, cross, dot): (Vector, Vector, Vector, Vector, Number) = f.call((a, b))?;
+    assert_eq!(sum, a + b);
+    assert_eq!(mul, a * 2.0);
+    assert_eq!(mul2, a * 2.0);
+    assert_eq!(cross, a.cross(b));
+    assert_eq!(dot, 10.0);
+
+    // Metamethods called directly with two numbers
+    let add = lua
+        .load("function(v) return getmetatable(v).__add(1, 2) end")
+        .eval::<Function>()?;
+    assert!(add.call::<Vector>(a).is_err());
+
+    Ok(())
+}
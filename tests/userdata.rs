@@ -428,6 +428,91 @@ fn test_userdata_destroy() -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "luau")]
+#[test]
+fn test_userdata_tags() -> Result<()> {
+    struct MyUserdata<const N: usize>(#[allow(unused)] Arc<()>);
+
+    impl<const N: usize> UserData for MyUserdata<N> {
+        fn add_methods<M: UserDataMethods<Self>>(methods: &mut M) {
+            methods.add_method("n", |_, _, ()| Ok(N));
+        }
+    }
+
+    fn check<const N: usize>(lua: &Lua, rc: &Arc<()>) -> Result<()> {
+        let ud = lua.create_userdata(MyUserdata::<N>(rc.clone()))?;
+        assert!(ud.is::<MyUserdata<N>>());
+        assert_eq!(ud.is::<MyUserdata<0>>(), N == 0);
+        assert_eq!(ud.call_method::<usize>("n", ())?, N);
+        assert_eq!(ud.borrow::<MyUserdata<0>>().is_ok(), N == 0);
+        if N % 2 == 0 {
+            ud.take::<MyUserdata<N>>()?;
+            assert!(matches!(ud.borrow::<MyUserdata<N>>(), Err(Error::UserDataDestructed)));
+        }
+        Ok(())
+    }
+
+    macro_rules! check_tens {
+        ($lua:expr, $rc:expr, $($t:literal),*) => {
+            $(
+                check::<{ $t * 10 }>($lua, $rc)?;
+                check::<{ $t * 10 + 1 }>($lua, $rc)?;
+                check::<{ $t * 10 + 2 }>($lua, $rc)?;
+                check::<{ $t * 10 + 3 }>($lua, $rc)?;
+                check::<{ $t * 10 + 4 }>($lua, $rc)?;
+                check::<{ $t * 10 + 5 }>($lua, $rc)?;
+                check::<{ $t * 10 + 6 }>($lua, $rc)?;
+                check::<{ $t * 10 + 7 }>($lua, $rc)?;
+                check::<{ $t * 10 + 8 }>($lua, $rc)?;
+                check::<{ $t * 10 + 9 }>($lua, $rc)?;
+            )*
+        };
+    }
+
+    // Register more types than available Luau userdata tags
+    let lua = Lua::new();
+    let rc = Arc::new(());
+    check_tens!(&lua, &rc, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14);
+
+    lua.gc_collect()?;
+    lua.gc_collect()?;
+    assert_eq!(Arc::strong_count(&rc), 1);
+
+    Ok(())
+}
+
+#[cfg(feature = "luau")]
+#[test]
+fn test_userdata_foreign_tag() -> Result<()> {
+    use mlua::ffi;
+
+    struct MyUserData(#[allow(unused)] i64);
+
+    impl UserData for MyUserData {}
+
+    let lua = Lua::new();
+    let ud = lua.create_userdata(MyUserData(1))?;
+
+    // Create userdata with the same tag but a different metatable
+    let foreign: AnyUserData = unsafe {
+        lua.exec_raw(&ud, |state| {
+            let tag = ffi::lua_userdatatag(state, -1);
+            ffi::lua_pop(state, 1);
+            ffi::lua_newuserdatatagged(state, 8, tag);
+            ffi::lua_newtable(state);
+            ffi::lua_setmetatable(state, -2);
+        })
+    }?;
+    assert!(ud.is::<MyUserData>());
+    assert!(!foreign.is::<MyUserData>());
+    assert!(matches!(foreign.borrow::<MyUserData>(), Err(Error::UserDataTypeMismatch)));
+
+    // Do not let the registered destructor run on the foreign userdata
+    unsafe { lua.exec_raw::<()>(&foreign, |state| ffi::lua_setuserdatatag(state, -1, 0)) }?;
+
+    Ok(())
+}
+
 #[test]
 fn test_user_values() -> Result<()> {
     struct MyUserData;